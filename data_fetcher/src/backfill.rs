@@ -0,0 +1,205 @@
+use crate::{
+    error::FetchError,
+    manifest::FetchManifest,
+    pipeline::{fetch_concurrently, FetchOutcome, FetchReport},
+    query::{PtMeasureType, Season, SeasonType, StatsQuery},
+    transport::Transport,
+    Endpoint, FetchContext, OutputDirs,
+};
+use std::{sync::Arc, time::Duration};
+use tracing::info;
+
+/// Endpoints that are meaningful for past seasons. Injuries, odds and the
+/// live scoreboard only ever describe today, so they are not backfilled.
+/// The shot chart is, but only when opted into like in a regular run; see
+/// [`crate::OPT_IN_ENDPOINTS`].
+pub const BACKFILL_ENDPOINTS: [Endpoint; 27] = [
+    Endpoint::PlayerIndex,
+    Endpoint::PlayerGeneralAverages,
+    Endpoint::TeamsGeneralAdvanced,
+    Endpoint::TeamsGeneralOpponent,
+    Endpoint::OpponentShootingGeneral,
+    Endpoint::PlayerGameLogs,
+    Endpoint::TeamGameLogs,
+    Endpoint::Lineups,
+    Endpoint::PlayerOnOff,
+    Endpoint::Standings,
+    Endpoint::PlayerTracking(PtMeasureType::SpeedDistance),
+    Endpoint::PlayerTracking(PtMeasureType::Possessions),
+    Endpoint::PlayerTracking(PtMeasureType::Drives),
+    Endpoint::PlayerTracking(PtMeasureType::CatchShoot),
+    Endpoint::PlayerTracking(PtMeasureType::PullUpShot),
+    Endpoint::PlayerTracking(PtMeasureType::Rebounding),
+    Endpoint::PlayerTracking(PtMeasureType::Defense),
+    Endpoint::TeamTracking(PtMeasureType::SpeedDistance),
+    Endpoint::TeamTracking(PtMeasureType::Possessions),
+    Endpoint::TeamTracking(PtMeasureType::Drives),
+    Endpoint::TeamTracking(PtMeasureType::CatchShoot),
+    Endpoint::TeamTracking(PtMeasureType::PullUpShot),
+    Endpoint::TeamTracking(PtMeasureType::Rebounding),
+    Endpoint::TeamTracking(PtMeasureType::Defense),
+    Endpoint::PlayerHustle,
+    Endpoint::TeamHustle,
+    Endpoint::SeasonSchedule,
+];
+
+/// The first season `endpoint` has data for, if it doesn't go back as far
+/// as the others: player tracking started in 2013-14 and hustle stats in
+/// 2015-16.
+pub fn first_season(endpoint: Endpoint) -> Option<Season> {
+    match endpoint {
+        Endpoint::PlayerTracking(_)
+        | Endpoint::TeamTracking(_)
+        | Endpoint::OpponentShootingGeneral => Some(Season::new(2013)),
+        Endpoint::PlayerHustle | Endpoint::TeamHustle => Some(Season::new(2015)),
+        _ => None,
+    }
+}
+
+/// Fetches `endpoints`, usually [`BACKFILL_ENDPOINTS`], for every season
+/// from `from` to `to` (inclusive) into its own partition under
+/// `data/history`, `concurrency` requests at a time.
+///
+/// Files already present in a partition are left alone, so an interrupted
+/// backfill can simply be re-run and completed seasons cost nothing.
+pub async fn backfill_seasons(
+    from: Season,
+    to: Season,
+    season_type: SeasonType,
+    endpoints: &[Endpoint],
+    transport: Arc<dyn Transport>,
+    concurrency: usize,
+) -> Result<Vec<(Season, FetchReport)>, FetchError> {
+    let mut reports = Vec::new();
+    let mut season = from;
+    while season <= to {
+        let query = StatsQuery::new()
+            .with_season(season)
+            .with_season_type(season_type);
+        let dirs = OutputDirs::under(&Endpoint::history_data_file_path(season, season_type));
+        let ctx = FetchContext::new(query, dirs).with_transport(Arc::clone(&transport));
+        reports.push((season, backfill_season(&ctx, endpoints, concurrency).await?));
+        season = season.next();
+    }
+    Ok(reports)
+}
+
+async fn backfill_season(
+    ctx: &FetchContext,
+    endpoints: &[Endpoint],
+    concurrency: usize,
+) -> Result<FetchReport, FetchError> {
+    ctx.dirs.create()?;
+
+    let season = ctx.query.season();
+    let (tracked, untracked): (Vec<Endpoint>, Vec<Endpoint>) = endpoints
+        .iter()
+        .partition(|endpoint| first_season(**endpoint).is_none_or(|first| first <= season));
+    let (on_disk, missing): (Vec<Endpoint>, Vec<Endpoint>) = tracked
+        .into_iter()
+        .partition(|endpoint| is_on_disk(endpoint, &ctx.dirs));
+
+    if missing.is_empty() {
+        info!("Season {} already on disk, skipping", season);
+    } else {
+        info!("Backfilling {:?} for season {}", missing, season);
+    }
+
+    let mut report = FetchReport::default();
+    for endpoint in untracked {
+        let first = first_season(endpoint).expect("only limited endpoints are untracked");
+        report.push(
+            endpoint,
+            FetchOutcome::Skipped(format!("not tracked before {}", first)),
+            Duration::ZERO,
+        );
+    }
+    for endpoint in on_disk {
+        report.push(
+            endpoint,
+            FetchOutcome::Skipped("already on disk".to_string()),
+            Duration::ZERO,
+        );
+    }
+    report.extend(fetch_concurrently(&missing, ctx, concurrency).await);
+    FetchManifest::build(&report, ctx)?.write(&ctx.dirs.manifest())?;
+    Ok(report)
+}
+
+fn is_on_disk(endpoint: &Endpoint, dirs: &OutputDirs) -> bool {
+    let mut path = dirs.prepared.join(endpoint.file_name());
+    path.set_extension(endpoint.file_extension());
+    path.exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::FixtureTransport;
+    use std::{
+        env,
+        fs::{self, File},
+    };
+
+    #[test]
+    fn is_on_disk_checks_the_prepared_file() {
+        let dir = env::temp_dir().join("nba_backfill_is_on_disk");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let schedule = dirs.prepared.join("fetched_SeasonSchedule_data.json");
+
+        assert!(!is_on_disk(&Endpoint::SeasonSchedule, &dirs));
+        File::create(&schedule).unwrap();
+        assert!(is_on_disk(&Endpoint::SeasonSchedule, &dirs));
+        assert!(!is_on_disk(&Endpoint::PlayerIndex, &dirs));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn endpoints_are_only_fetched_for_seasons_they_cover() {
+        let dir = env::temp_dir().join("nba_backfill_first_season");
+        let _ = fs::remove_dir_all(&dir);
+        let query = StatsQuery::new().with_season(Season::new(2014));
+        let ctx = FetchContext::new(query, OutputDirs::under(&dir))
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        let report = backfill_season(&ctx, &BACKFILL_ENDPOINTS, 4).await.unwrap();
+
+        let outcome = |endpoint| {
+            &report
+                .endpoints
+                .iter()
+                .find(|r| r.endpoint == endpoint)
+                .unwrap()
+                .outcome
+        };
+        assert!(matches!(
+            outcome(Endpoint::PlayerHustle),
+            FetchOutcome::Skipped(reason) if reason == "not tracked before 2015-16"
+        ));
+        assert!(matches!(
+            outcome(Endpoint::PlayerTracking(PtMeasureType::Drives)),
+            FetchOutcome::Success
+        ));
+        // The league-wide shot chart is opt-in.
+        assert!(!report
+            .endpoints
+            .iter()
+            .any(|r| r.endpoint == Endpoint::ShotChart));
+        assert!(!ctx
+            .dirs
+            .prepared
+            .join("fetched_PlayerHustle_data.csv")
+            .exists());
+
+        assert_eq!(
+            first_season(Endpoint::TeamTracking(PtMeasureType::Defense)),
+            Some(Season::new(2013))
+        );
+        assert_eq!(first_season(Endpoint::PlayerIndex), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
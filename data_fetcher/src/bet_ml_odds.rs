@@ -1,78 +1,309 @@
-use csv::Writer;
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 
-use crate::{error::FetchError, fetch_data, Endpoint};
-use std::fs::File;
+use crate::{
+    error::FetchError,
+    market_odds::{GameRow, Line, OddsMarket, OddsPage, OddsScope},
+    next_build::fetch_odds_page,
+    result_sets::write_csv_with_header,
+    Endpoint, FetchContext, OutputDirs,
+};
+use std::path::PathBuf;
 
-pub async fn fetch_bet_ml_odds() -> Result<(), FetchError> {
-    // API Endpoint
-    let endpoint = Endpoint::Bet365Odds.url();
+/// The book whose moneylines the prepared file holds, as `oddsViews` names it.
+const SPORTSBOOK: &str = "bet365";
+
+/// A moneyline at one point in time. Either side can be missing, e.g. while
+/// a book has the game off the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketLine {
+    pub away_odds: Option<i64>,
+    pub home_odds: Option<i64>,
+}
+
+impl From<&Line> for MarketLine {
+    fn from(line: &Line) -> Self {
+        MarketLine {
+            away_odds: line.away_odds,
+            home_odds: line.home_odds,
+        }
+    }
+}
+
+/// bet365's full-game moneyline for one game, one row of
+/// `fetched_Bet365Odds_data.csv`. `game_id` is sportsbookreview's, the
+/// start date is UTC and the opening prices are empty if the book never
+/// posted them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameOdds {
+    pub game_id: i64,
+    pub start_date: String,
+    pub away_team: String,
+    pub away_odds: Option<i64>,
+    pub open_away_odds: Option<i64>,
+    pub home_team: String,
+    pub home_odds: Option<i64>,
+    pub open_home_odds: Option<i64>,
+}
+
+impl GameOdds {
+    /// The fields, as the header of `fetched_Bet365Odds_data.csv`.
+    pub const COLUMNS: [&'static str; 8] = [
+        "game_id",
+        "start_date",
+        "away_team",
+        "away_odds",
+        "open_away_odds",
+        "home_team",
+        "home_odds",
+        "open_home_odds",
+    ];
+
+    pub fn current(&self) -> MarketLine {
+        MarketLine {
+            away_odds: self.away_odds,
+            home_odds: self.home_odds,
+        }
+    }
+
+    pub fn opening(&self) -> Option<MarketLine> {
+        let line = MarketLine {
+            away_odds: self.open_away_odds,
+            home_odds: self.open_home_odds,
+        };
+        (line.away_odds.is_some() || line.home_odds.is_some()).then_some(line)
+    }
+}
+
+/// A game left out of the prepared file and why, one row of
+/// `fetched_Bet365Odds_skipped.csv`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedGame {
+    pub game_id: i64,
+    pub matchup: String,
+    pub reason: String,
+}
+
+impl SkippedGame {
+    /// The fields, as the header of `fetched_Bet365Odds_skipped.csv`.
+    pub const COLUMNS: [&'static str; 3] = ["game_id", "matchup", "reason"];
+}
+
+/// The games left out of `fetched_Bet365Odds_data.csv`, next to it.
+pub fn bet365_skipped_path(dirs: &OutputDirs) -> PathBuf {
+    dirs.prepared.join("fetched_Bet365Odds_skipped.csv")
+}
+
+/// bet365's line for every game on the page. Games the book has no current
+/// line for, e.g. pulled lines, are returned separately instead.
+fn game_odds(page: &OddsPage) -> (Vec<GameOdds>, Vec<SkippedGame>) {
+    let mut games = Vec::new();
+    let mut skipped = Vec::new();
+    let rows = page
+        .page_props
+        .odds_tables
+        .iter()
+        .flat_map(|table| &table.odds_table_model.game_rows);
+    for row in rows {
+        match row_odds(row) {
+            Ok(odds) => games.push(odds),
+            Err(reason) => skipped.push(SkippedGame {
+                game_id: row.game_view.game_id,
+                matchup: format!(
+                    "{} @ {}",
+                    row.game_view.away_team.full_name, row.game_view.home_team.full_name
+                ),
+                reason: reason.to_string(),
+            }),
+        }
+    }
+    (games, skipped)
+}
+
+fn row_odds(row: &GameRow) -> Result<GameOdds, &'static str> {
+    let view = row
+        .odds_views
+        .iter()
+        .flatten()
+        .find(|view| view.sportsbook == SPORTSBOOK)
+        .ok_or("no bet365 odds")?;
+    let current: MarketLine = view.current_line.as_ref().ok_or("no current line")?.into();
+    if current.away_odds.is_none() && current.home_odds.is_none() {
+        return Err("no current prices");
+    }
+    let opening = view.opening_line.as_ref().map(MarketLine::from);
+
+    let game = &row.game_view;
+    Ok(GameOdds {
+        game_id: game.game_id,
+        start_date: game.start_date.clone(),
+        away_team: game.away_team.full_name.clone(),
+        away_odds: current.away_odds,
+        open_away_odds: opening.and_then(|line| line.away_odds),
+        home_team: game.home_team.full_name.clone(),
+        home_odds: current.home_odds,
+        open_home_odds: opening.and_then(|line| line.home_odds),
+    })
+}
+
+pub async fn fetch_bet_ml_odds(ctx: &FetchContext) -> Result<(), FetchError> {
+    // Fetch Data
+    let page: OddsPage = fetch_odds_page(
+        ctx,
+        Endpoint::Bet365Odds,
+        OddsMarket::MoneyLine,
+        OddsScope::FullGame,
+    )
+    .await?;
+    write_bet_ml_odds(&page, &ctx.dirs)
+}
+
+/// Writes bet365's lines on `page`, the full-game moneyline page, to
+/// `fetched_Bet365Odds_data.csv`, and the games it has none for to
+/// [`bet365_skipped_path`].
+pub(crate) fn write_bet_ml_odds(page: &OddsPage, dirs: &OutputDirs) -> Result<(), FetchError> {
     // File Name for future reference
     let file_name = Endpoint::Bet365Odds.file_name();
 
-    // Fetch Data
-    let data: Value = fetch_data(endpoint, file_name).await?;
-
-    let game_odds = data.get("pageProps").unwrap().get("oddsTables").unwrap()[0]
-        .get("oddsTableModel")
-        .unwrap()
-        .get("gameRows")
-        .unwrap()
-        .as_array()
-        .unwrap();
-
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
+    let (games, skipped) = game_odds(page);
+    for game in &skipped {
+        warn!(
+            "Skipping {} ({}): {}",
+            game.matchup, game.game_id, game.reason
+        );
+    }
+
+    let mut save_file_path = dirs.prepared.join(file_name);
     save_file_path.set_extension("csv");
 
-    let file = File::create(save_file_path)?;
-    let mut wtr = Writer::from_writer(file);
+    write_csv_with_header(&GameOdds::COLUMNS, games, save_file_path)?;
+    write_csv_with_header(&SkippedGame::COLUMNS, skipped, bet365_skipped_path(dirs))?;
+    Ok(())
+}
 
-    for game in game_odds {
-        let current_line = game.get("oddsViews").unwrap()[3]
-            .get("currentLine")
-            .unwrap();
-        let opening_line = game.get("oddsViews").unwrap()[3]
-            .get("openingLine")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport};
+    use serde_json::Value;
+    use std::{env, fs, sync::Arc};
+
+    fn seed_page() -> Value {
+        let path = Endpoint::data_file_path()
+            .join("seed_data")
+            .join("moneyLineFullGame.json");
+        serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn writes_one_row_per_game_with_a_header() {
+        let dir = env::temp_dir().join("nba_bet_ml_odds");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_bet_ml_odds(&ctx).await.unwrap();
+
+        let path = dirs.prepared.join("fetched_Bet365Odds_data.csv");
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec![
+                "game_id",
+                "start_date",
+                "away_team",
+                "away_odds",
+                "open_away_odds",
+                "home_team",
+                "home_odds",
+                "open_home_odds"
+            ]
+        );
+        let games: Vec<GameOdds> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(games.len(), 5);
+        assert_eq!(
+            games[0],
+            GameOdds {
+                game_id: 315632,
+                start_date: "2024-11-12T01:00:00+00:00".to_string(),
+                away_team: "Sacramento Kings".to_string(),
+                away_odds: Some(-130),
+                open_away_odds: Some(-115),
+                home_team: "San Antonio Spurs".to_string(),
+                home_odds: Some(110),
+                open_home_odds: Some(-105),
+            }
+        );
+        let skipped = fs::read_to_string(bet365_skipped_path(&dirs)).unwrap();
+        assert_eq!(skipped, "game_id,matchup,reason\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pulled_lines_are_skipped_and_listed() {
+        let mut page = seed_page();
+        let rows = page
+            .pointer_mut("/pageProps/oddsTables/0/oddsTableModel/gameRows")
             .unwrap();
+        // bet365 pulled game 2, never listed game 3 and has no opener or
+        // home price on game 4.
+        rows[1]["oddsViews"][3]["currentLine"] = Value::Null;
+        rows[2]["oddsViews"][3] = Value::Null;
+        rows[3]["oddsViews"][3]["openingLine"] = Value::Null;
+        rows[3]["oddsViews"][3]["currentLine"]["homeOdds"] = Value::Null;
+        let game_ids: Vec<i64> = (0..5)
+            .map(|i| rows[i]["gameView"]["gameId"].as_i64().unwrap())
+            .collect();
+        let page: OddsPage = serde_json::from_value(page).unwrap();
 
-        let away_odds = current_line.get("awayOdds").unwrap().to_string();
-        let open_away_odds = opening_line.get("awayOdds").unwrap().to_string();
-        let away_team_name = game
-            .get("gameView")
-            .unwrap()
-            .get("awayTeam")
-            .unwrap()
-            .get("name")
-            .unwrap()
-            .as_str()
-            .unwrap()
-            .to_string();
-        let home_odds = current_line.get("homeOdds").unwrap().to_string();
-        let open_home_odds = opening_line.get("homeOdds").unwrap().to_string();
-        let home_team_name = game
-            .get("gameView")
-            .unwrap()
-            .get("homeTeam")
-            .unwrap()
-            .get("name")
-            .unwrap()
-            .as_str()
+        let (games, _) = game_odds(&page);
+        assert_eq!(games.len(), 3);
+        let partial = games.iter().find(|g| g.game_id == game_ids[3]).unwrap();
+        assert_eq!(partial.current().home_odds, None);
+        assert!(partial.current().away_odds.is_some());
+        assert_eq!(partial.opening(), None);
+
+        let dir = env::temp_dir().join("nba_bet_ml_odds_skipped");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        write_bet_ml_odds(&page, &dirs).unwrap();
+        let mut reader = csv::Reader::from_path(bet365_skipped_path(&dirs)).unwrap();
+        let skipped: Vec<SkippedGame> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|s| (s.game_id, s.reason.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (game_ids[1], "no current line"),
+                (game_ids[2], "no bet365 odds")
+            ]
+        );
+        assert!(skipped[0].matchup.contains(" @ "));
+
+        // With every line pulled the odds file is still there, header only.
+        let mut page = seed_page();
+        let rows = page
+            .pointer_mut("/pageProps/oddsTables/0/oddsTableModel/gameRows")
             .unwrap()
-            .to_string();
-
-        let ml_odds = vec![
-            away_odds,
-            open_away_odds,
-            away_team_name,
-            home_odds,
-            open_home_odds,
-            home_team_name,
-        ];
-
-        wtr.write_record(ml_odds)?;
-    }
+            .as_array_mut()
+            .unwrap();
+        for row in rows.iter_mut() {
+            row["oddsViews"][3]["currentLine"] = Value::Null;
+        }
+        let page: OddsPage = serde_json::from_value(page).unwrap();
+        write_bet_ml_odds(&page, &dirs).unwrap();
+        let path = dirs.prepared.join("fetched_Bet365Odds_data.csv");
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        assert_eq!(reader.headers().unwrap(), &GameOdds::COLUMNS[..]);
+        assert_eq!(reader.records().count(), 0);
+        let mut reader = csv::Reader::from_path(bet365_skipped_path(&dirs)).unwrap();
+        assert_eq!(reader.records().count(), 5);
 
-    wtr.flush()?;
-    Ok(())
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
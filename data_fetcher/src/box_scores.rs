@@ -0,0 +1,277 @@
+use crate::{
+    error::FetchError, fetch_data, result_sets::write_vector_to_csv, Endpoint, FetchContext,
+    OutputDirs,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::BTreeSet, path::PathBuf};
+
+/// The box score endpoints, all keyed by game id.
+pub const BOX_SCORE_ENDPOINTS: [Endpoint; 4] = [
+    Endpoint::BoxScoreTraditional,
+    Endpoint::BoxScoreAdvanced,
+    Endpoint::BoxScoreFourFactors,
+    Endpoint::BoxScoreUsage,
+];
+
+#[derive(Deserialize, Debug)]
+#[allow(unused)]
+struct BoxScoreFetchedResponse {
+    meta: Value,
+    // Each endpoint names the same structure after itself.
+    #[serde(
+        rename = "boxScoreTraditional",
+        alias = "boxScoreAdvanced",
+        alias = "boxScoreFourFactors",
+        alias = "boxScoreUsage"
+    )]
+    box_score: BoxScoreGame,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxScoreGame {
+    pub game_id: String,
+    pub home_team: BoxScoreTeam,
+    pub away_team: BoxScoreTeam,
+}
+
+/// One side of a box score. `statistics` holds whichever measures the
+/// endpoint reports, keyed by their camelCase names, so the four kinds share
+/// one type.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxScoreTeam {
+    pub team_id: i64,
+    pub team_city: String,
+    pub team_name: String,
+    pub team_tricode: String,
+    pub players: Vec<BoxScorePlayer>,
+    pub statistics: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxScorePlayer {
+    pub person_id: i64,
+    pub first_name: String,
+    pub family_name: String,
+    /// Empty for players who came off the bench.
+    #[serde(default)]
+    pub position: String,
+    /// Why a player didn't play, e.g. `DNP - Coach's Decision`.
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub jersey_num: String,
+    pub statistics: Map<String, Value>,
+}
+
+impl BoxScoreGame {
+    fn sides(&self) -> [(&'static str, &BoxScoreTeam); 2] {
+        [("home", &self.home_team), ("away", &self.away_team)]
+    }
+}
+
+const TEAM_COLUMNS: [&str; 6] = [
+    "gameId",
+    "side",
+    "teamId",
+    "teamCity",
+    "teamName",
+    "teamTricode",
+];
+
+const PLAYER_COLUMNS: [&str; 10] = [
+    "gameId",
+    "side",
+    "teamId",
+    "teamTricode",
+    "personId",
+    "firstName",
+    "familyName",
+    "position",
+    "comment",
+    "jerseyNum",
+];
+
+/// Short name of a box score kind, used in its file names.
+fn kind(endpoint: Endpoint) -> Result<&'static str, FetchError> {
+    match endpoint {
+        Endpoint::BoxScoreTraditional => Ok("traditional"),
+        Endpoint::BoxScoreAdvanced => Ok("advanced"),
+        Endpoint::BoxScoreFourFactors => Ok("four_factors"),
+        Endpoint::BoxScoreUsage => Ok("usage"),
+        other => Err(FetchError::NotABoxScore(other)),
+    }
+}
+
+/// Stem of every file one box score writes, e.g. `0022400201_traditional`.
+pub fn box_score_file_name(endpoint: Endpoint, game_id: &str) -> Result<String, FetchError> {
+    Ok(format!("{}_{}", game_id, kind(endpoint)?))
+}
+
+/// The prepared `(players, teams)` CSVs for one box score.
+pub fn box_score_paths(
+    dirs: &OutputDirs,
+    endpoint: Endpoint,
+    game_id: &str,
+) -> Result<(PathBuf, PathBuf), FetchError> {
+    let stem = box_score_file_name(endpoint, game_id)?;
+    Ok((
+        dirs.prepared.join(format!("{}_players.csv", stem)),
+        dirs.prepared.join(format!("{}_teams.csv", stem)),
+    ))
+}
+
+/// Fetches the box score for the game in `ctx.query` and writes its
+/// players and teams CSVs.
+pub async fn fetch_box_score(endpoint: Endpoint, ctx: &FetchContext) -> Result<(), FetchError> {
+    let game_id = ctx
+        .query
+        .game_id()
+        .ok_or(FetchError::MissingGameId(endpoint))?;
+    let url = endpoint.url(&ctx.query);
+    let file_name = box_score_file_name(endpoint, game_id)?;
+
+    let data: BoxScoreFetchedResponse = fetch_data(ctx, endpoint, &url, &file_name).await?;
+
+    let (players_path, teams_path) = box_score_paths(&ctx.dirs, endpoint, game_id)?;
+    // Players last: per_game treats that file as the box score being done.
+    write_teams(&data.box_score, teams_path)?;
+    write_players(&data.box_score, players_path)
+}
+
+/// Every statistic that appears in any of `statistics`, sorted, so a
+/// missing one becomes an empty cell instead of a shifted column.
+fn stat_columns<'a>(statistics: impl Iterator<Item = &'a Map<String, Value>>) -> Vec<String> {
+    let columns: BTreeSet<&String> = statistics.flat_map(Map::keys).collect();
+    columns.into_iter().cloned().collect()
+}
+
+fn with_stats(
+    mut row: Vec<Value>,
+    columns: &[String],
+    statistics: &Map<String, Value>,
+) -> Vec<Value> {
+    row.extend(
+        columns
+            .iter()
+            .map(|c| statistics.get(c).cloned().unwrap_or(Value::Null)),
+    );
+    row
+}
+
+fn headers(identity: &[&str], stats: &[String]) -> Vec<String> {
+    identity
+        .iter()
+        .map(|c| c.to_string())
+        .chain(stats.iter().cloned())
+        .collect()
+}
+
+fn write_teams(game: &BoxScoreGame, path: PathBuf) -> Result<(), FetchError> {
+    let columns = stat_columns(game.sides().into_iter().map(|(_, t)| &t.statistics));
+    let rows = game
+        .sides()
+        .into_iter()
+        .map(|(side, team)| {
+            let identity = vec![
+                game.game_id.clone().into(),
+                side.into(),
+                team.team_id.into(),
+                team.team_city.clone().into(),
+                team.team_name.clone().into(),
+                team.team_tricode.clone().into(),
+            ];
+            with_stats(identity, &columns, &team.statistics)
+        })
+        .collect();
+    write_vector_to_csv(Some(headers(&TEAM_COLUMNS, &columns)), rows, path)
+}
+
+fn write_players(game: &BoxScoreGame, path: PathBuf) -> Result<(), FetchError> {
+    let columns = stat_columns(
+        game.sides()
+            .into_iter()
+            .flat_map(|(_, t)| t.players.iter().map(|p| &p.statistics)),
+    );
+    let mut rows = Vec::new();
+    for (side, team) in game.sides() {
+        for player in &team.players {
+            let identity = vec![
+                game.game_id.clone().into(),
+                side.into(),
+                team.team_id.into(),
+                team.team_tricode.clone().into(),
+                player.person_id.into(),
+                player.first_name.clone().into(),
+                player.family_name.clone().into(),
+                player.position.clone().into(),
+                player.comment.clone().into(),
+                player.jersey_num.clone().into(),
+            ];
+            rows.push(with_stats(identity, &columns, &player.statistics));
+        }
+    }
+    write_vector_to_csv(Some(headers(&PLAYER_COLUMNS, &columns)), rows, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{per_game::fetch_games, query::StatsQuery, transport::FixtureTransport};
+    use std::{env, fs, sync::Arc};
+
+    #[tokio::test]
+    async fn box_scores_are_fetched_once_per_game() {
+        let dir = env::temp_dir().join("nba_box_scores");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+        let game_ids = vec!["0022400201".to_string()];
+
+        let report = fetch_games(&game_ids, &BOX_SCORE_ENDPOINTS, &ctx, 2)
+            .await
+            .unwrap();
+        assert!(report.is_success(), "{}", report);
+        assert_eq!(report.fetched, 4);
+
+        let (players, teams) =
+            box_score_paths(&dirs, Endpoint::BoxScoreTraditional, "0022400201").unwrap();
+        let mut reader = csv::Reader::from_path(&players).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        assert!(headers.iter().take(PLAYER_COLUMNS.len()).eq(PLAYER_COLUMNS));
+        let points = headers.iter().position(|h| h == "points").unwrap();
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(&rows[0][6], "Cunningham");
+        assert_eq!(&rows[0][points], "25");
+
+        let teams = fs::read_to_string(teams).unwrap();
+        assert!(teams
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("0022400201,home,1610612765"));
+        assert!(dirs.raw.join("0022400201_usage.txt").exists());
+
+        let again = fetch_games(&game_ids, &BOX_SCORE_ENDPOINTS, &ctx, 2)
+            .await
+            .unwrap();
+        assert_eq!((again.fetched, again.on_disk), (0, 4));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn box_score_needs_a_game_id() {
+        let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::under(&env::temp_dir()))
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+        assert!(matches!(
+            fetch_box_score(Endpoint::BoxScoreUsage, &ctx).await,
+            Err(FetchError::MissingGameId(Endpoint::BoxScoreUsage))
+        ));
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    client::{shared_client, Conditional, HttpClient, Validators},
+    error::FetchError,
+    transport::{fixture_file_name, Response, Transport, TransportFuture},
+    Endpoint,
+};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+pub fn http_cache_file_path() -> PathBuf {
+    // "../data/http_cache"
+    Endpoint::data_file_path().join("http_cache")
+}
+
+/// How long a cached response is used without asking the server at all.
+/// Past that it is revalidated with a conditional request.
+pub fn default_ttl(endpoint: Endpoint) -> Duration {
+    match endpoint {
+        Endpoint::PlayerIndex | Endpoint::SeasonSchedule => Duration::from_secs(6 * 60 * 60),
+        Endpoint::PlayerGeneralAverages
+        | Endpoint::TeamsGeneralAdvanced
+        | Endpoint::TeamsGeneralOpponent
+        | Endpoint::OpponentShootingGeneral
+        | Endpoint::PlayerGameLogs
+        | Endpoint::TeamGameLogs
+        | Endpoint::Lineups
+        | Endpoint::PlayerOnOff
+        | Endpoint::Standings
+        | Endpoint::PlayerTracking(_)
+        | Endpoint::TeamTracking(_)
+        | Endpoint::PlayerHustle
+        | Endpoint::TeamHustle
+        | Endpoint::ShotChart => Duration::from_secs(60 * 60),
+        // Only ever fetched for finished games.
+        Endpoint::BoxScoreTraditional
+        | Endpoint::BoxScoreAdvanced
+        | Endpoint::BoxScoreFourFactors
+        | Endpoint::BoxScoreUsage
+        | Endpoint::PlayByPlay => Duration::from_secs(7 * 24 * 60 * 60),
+        // Change by the minute on game days.
+        Endpoint::TodaysScoreboard
+        | Endpoint::InjuryReport
+        | Endpoint::Bet365Odds
+        | Endpoint::Odds(..)
+        | Endpoint::OddsLandingPage => Duration::ZERO,
+    }
+}
+
+/// How a response was served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// Downloaded; nothing usable was cached.
+    Miss,
+    /// Served from the cache without a request, within the endpoint's TTL.
+    Fresh,
+    /// The server answered `304 Not Modified` to a conditional request.
+    Revalidated,
+}
+
+impl CacheStatus {
+    pub fn is_hit(&self) -> bool {
+        !matches!(self, CacheStatus::Miss)
+    }
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CacheStatus::Miss => "downloaded",
+            CacheStatus::Fresh => "cached",
+            CacheStatus::Revalidated => "not modified",
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    stored_at: DateTime<Utc>,
+    validators: Validators,
+}
+
+/// Sends requests through the HTTP client but keeps each response on disk,
+/// one directory per `Endpoint`, and reuses it while it is fresh or the
+/// server says it hasn't changed.
+#[derive(Debug)]
+pub struct CachingTransport {
+    dir: PathBuf,
+    client: Option<HttpClient>,
+    ttls: HashMap<Endpoint, Duration>,
+    statuses: Mutex<HashMap<Endpoint, CacheStatus>>,
+}
+
+impl CachingTransport {
+    /// Caches under `dir`, fetching through the shared client.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CachingTransport {
+            dir: dir.into(),
+            client: None,
+            ttls: HashMap::new(),
+            statuses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_client(mut self, client: HttpClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Overrides [`default_ttl`] for `endpoint`.
+    pub fn with_ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint, ttl);
+        self
+    }
+
+    pub fn ttl(&self, endpoint: Endpoint) -> Duration {
+        self.ttls
+            .get(&endpoint)
+            .copied()
+            .unwrap_or_else(|| default_ttl(endpoint))
+    }
+
+    fn client(&self) -> &HttpClient {
+        self.client.as_ref().unwrap_or_else(|| shared_client())
+    }
+
+    /// Entries for one URL: `<key>.json` holds the validators, `<key>.body`
+    /// the response.
+    fn paths(&self, endpoint: Endpoint, url: &str) -> (PathBuf, PathBuf) {
+        let endpoint_dir = self.dir.join(
+            Path::new(fixture_file_name(endpoint))
+                .file_stem()
+                .expect("fixture names have a stem"),
+        );
+        let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+        let key = &key[..16];
+        (
+            endpoint_dir.join(format!("{}.json", key)),
+            endpoint_dir.join(format!("{}.body", key)),
+        )
+    }
+
+    async fn fetch(&self, endpoint: Endpoint, url: &str) -> Result<Response, FetchError> {
+        let (meta_path, body_path) = self.paths(endpoint, url);
+        let cached = read_entry(&meta_path, &body_path)
+            .inspect_err(|e| warn!("Ignoring unreadable cache entry {:?}: {}", meta_path, e))
+            .ok()
+            .flatten()
+            .filter(|(entry, _)| entry.url == url);
+
+        if let Some((entry, body)) = &cached {
+            let age = (Utc::now() - entry.stored_at).to_std().unwrap_or_default();
+            if age < self.ttl(endpoint) {
+                debug!("Serving {} from cache ({:?} old)", url, age);
+                self.set_status(endpoint, CacheStatus::Fresh);
+                return Ok(Response {
+                    status: None,
+                    body: body.clone(),
+                });
+            }
+        }
+
+        let validators = cached
+            .as_ref()
+            .map(|(entry, _)| entry.validators.clone())
+            .unwrap_or_default();
+        match (
+            self.client().get_conditional(url, &validators).await?,
+            cached,
+        ) {
+            (Conditional::NotModified, Some((mut entry, body))) => {
+                entry.stored_at = Utc::now();
+                write_meta(&meta_path, &entry)?;
+                self.set_status(endpoint, CacheStatus::Revalidated);
+                Ok(Response {
+                    status: Some(StatusCode::NOT_MODIFIED),
+                    body,
+                })
+            }
+            (Conditional::NotModified, None) => {
+                Err(FetchError::UnexpectedStatusCode(StatusCode::NOT_MODIFIED))
+            }
+            (
+                Conditional::Modified {
+                    status,
+                    body,
+                    validators,
+                },
+                _,
+            ) => {
+                let entry = CacheEntry {
+                    url: url.to_string(),
+                    stored_at: Utc::now(),
+                    validators,
+                };
+                fs::create_dir_all(body_path.parent().expect("entry has a directory"))?;
+                fs::write(&body_path, &body)?;
+                write_meta(&meta_path, &entry)?;
+                self.set_status(endpoint, CacheStatus::Miss);
+                Ok(Response {
+                    status: Some(status),
+                    body,
+                })
+            }
+        }
+    }
+
+    fn set_status(&self, endpoint: Endpoint, status: CacheStatus) {
+        self.statuses.lock().unwrap().insert(endpoint, status);
+    }
+}
+
+impl Transport for CachingTransport {
+    fn get<'a>(&'a self, endpoint: Endpoint, url: &'a str) -> TransportFuture<'a> {
+        Box::pin(self.fetch(endpoint, url))
+    }
+
+    fn take_cache_status(&self, endpoint: Endpoint) -> Option<CacheStatus> {
+        self.statuses.lock().unwrap().remove(&endpoint)
+    }
+}
+
+fn read_entry(meta_path: &Path, body_path: &Path) -> io::Result<Option<(CacheEntry, Vec<u8>)>> {
+    let meta = match fs::read(meta_path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let entry: CacheEntry = serde_json::from_slice(&meta)?;
+    Ok(Some((entry, fs::read(body_path)?)))
+}
+
+fn write_meta(path: &Path, entry: &CacheEntry) -> io::Result<()> {
+    fs::write(path, serde_json::to_vec_pretty(entry)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{test_client, MockResponse, MockServer};
+    use std::env;
+
+    fn transport(dir: &Path, ttl: Duration) -> CachingTransport {
+        CachingTransport::new(dir)
+            .with_client(test_client())
+            .with_ttl(Endpoint::PlayerIndex, ttl)
+    }
+
+    #[tokio::test]
+    async fn fresh_entries_skip_the_network() {
+        let dir = env::temp_dir().join("nba_http_cache_fresh");
+        let _ = fs::remove_dir_all(&dir);
+        let server = MockServer::start().await;
+        server.route("/stats/playerindex", vec![MockResponse::ok("{\"v\":1}")]);
+        let url = format!("{}/stats/playerindex?Season=2024-25", server.origin());
+
+        let cache = transport(&dir, Duration::from_secs(60));
+        for (expected, status) in [
+            (CacheStatus::Miss, Some(StatusCode::OK)),
+            (CacheStatus::Fresh, None),
+        ] {
+            let response = cache.get(Endpoint::PlayerIndex, &url).await.unwrap();
+            assert_eq!(response.body, b"{\"v\":1}");
+            assert_eq!(response.status, status);
+            assert_eq!(
+                cache.take_cache_status(Endpoint::PlayerIndex),
+                Some(expected)
+            );
+        }
+        assert_eq!(server.hits("/stats/playerindex"), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stale_entries_are_revalidated() {
+        let dir = env::temp_dir().join("nba_http_cache_stale");
+        let _ = fs::remove_dir_all(&dir);
+        let server = MockServer::start().await;
+        server.route(
+            "/stats/playerindex",
+            vec![
+                MockResponse::ok("{\"v\":1}").with_header("ETag", "\"abc\""),
+                MockResponse::not_modified(),
+                MockResponse::ok("{\"v\":2}").with_header("ETag", "\"def\""),
+            ],
+        );
+        let url = format!("{}/stats/playerindex", server.origin());
+
+        let cache = transport(&dir, Duration::ZERO);
+        let statuses = [
+            (CacheStatus::Miss, StatusCode::OK, "{\"v\":1}"),
+            (
+                CacheStatus::Revalidated,
+                StatusCode::NOT_MODIFIED,
+                "{\"v\":1}",
+            ),
+            (CacheStatus::Miss, StatusCode::OK, "{\"v\":2}"),
+        ];
+        for (expected, status, body) in statuses {
+            let got = cache.get(Endpoint::PlayerIndex, &url).await.unwrap();
+            assert_eq!(got.body, body.as_bytes());
+            assert_eq!(got.status, Some(status));
+            assert_eq!(
+                cache.take_cache_status(Endpoint::PlayerIndex),
+                Some(expected)
+            );
+        }
+
+        let requests = server.requests("/stats/playerindex");
+        assert!(!requests[0].to_lowercase().contains("if-none-match"));
+        assert!(requests[1]
+            .to_lowercase()
+            .contains("if-none-match: \"abc\""));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
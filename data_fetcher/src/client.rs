@@ -0,0 +1,516 @@
+use crate::{build_headers, error::FetchError, transport::Response};
+use rand::Rng;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+    },
+    Client, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+use tokio::{sync::Mutex, time::sleep};
+use tracing::warn;
+
+/// Timeouts, retry policy and rate limit for the shared HTTP client.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Sustained requests per second allowed against any one host.
+    pub requests_per_second: f64,
+    /// Requests that may be sent back to back before the rate limit applies.
+    pub burst: u32,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            max_retries: 4,
+            base_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            requests_per_second: 1.0,
+            burst: 3,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Defaults overridden by any of `NBA_CONNECT_TIMEOUT_SECS`,
+    /// `NBA_READ_TIMEOUT_SECS`, `NBA_MAX_RETRIES` and
+    /// `NBA_REQUESTS_PER_SECOND` that are set. A value that doesn't parse or
+    /// isn't usable is an error rather than silently ignored.
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        fn var<T: std::str::FromStr>(
+            lookup: &dyn Fn(&str) -> Option<String>,
+            name: &str,
+        ) -> Result<Option<T>, String> {
+            match lookup(name) {
+                Some(value) => value
+                    .trim()
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("{} has an invalid value {:?}", name, value)),
+                None => Ok(None),
+            }
+        }
+
+        let mut config = ClientConfig::default();
+        if let Some(secs) = var(&lookup, "NBA_CONNECT_TIMEOUT_SECS")? {
+            config.connect_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = var(&lookup, "NBA_READ_TIMEOUT_SECS")? {
+            config.read_timeout = Duration::from_secs(secs);
+        }
+        if let Some(retries) = var(&lookup, "NBA_MAX_RETRIES")? {
+            config.max_retries = retries;
+        }
+        if let Some(rate) = var(&lookup, "NBA_REQUESTS_PER_SECOND")? {
+            config.requests_per_second = rate;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Rejects settings no request could succeed with: a zero timeout or a
+    /// rate the rate limiter can't work with.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.connect_timeout.is_zero() {
+            return Err("NBA_CONNECT_TIMEOUT_SECS must be at least 1".to_string());
+        }
+        if self.read_timeout.is_zero() {
+            return Err("NBA_READ_TIMEOUT_SECS must be at least 1".to_string());
+        }
+        if !(self.requests_per_second.is_finite() && self.requests_per_second > 0.0) {
+            return Err(format!(
+                "NBA_REQUESTS_PER_SECOND must be a positive number, got {}",
+                self.requests_per_second
+            ));
+        }
+        Ok(())
+    }
+}
+
+static SHARED_CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+/// Installs `config` for the shared client. Returns `false` if the client was
+/// already in use, in which case the existing configuration is kept.
+pub fn init_shared_client(config: ClientConfig) -> bool {
+    SHARED_CLIENT.set(HttpClient::new(config)).is_ok()
+}
+
+/// The client every fetch goes through, created with the default
+/// configuration unless [`init_shared_client`] ran first.
+pub fn shared_client() -> &'static HttpClient {
+    SHARED_CLIENT.get_or_init(|| HttpClient::new(ClientConfig::default()))
+}
+
+/// A `reqwest::Client` with per-host rate limiting and retries.
+#[derive(Debug)]
+pub struct HttpClient {
+    client: Client,
+    config: ClientConfig,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl HttpClient {
+    pub fn new(config: ClientConfig) -> Self {
+        let client = Client::builder()
+            .default_headers(build_headers())
+            .connect_timeout(config.connect_timeout)
+            .read_timeout(config.read_timeout)
+            .build()
+            .expect("HTTP client configuration is valid");
+        HttpClient {
+            client,
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// GETs `url`, retrying timeouts, connection errors, 429 and 5xx
+    /// responses with exponential backoff, and returns the raw body.
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>, FetchError> {
+        Ok(self.get_response(url).await?.body)
+    }
+
+    /// Like [`get_bytes`](Self::get_bytes), but keeps the status the body
+    /// came with.
+    pub async fn get_response(&self, url: &str) -> Result<Response, FetchError> {
+        let (status, _, body) = self.send(url, HeaderMap::new()).await?;
+        Ok(Response {
+            status: Some(status),
+            body,
+        })
+    }
+
+    /// Like [`get_bytes`](Self::get_bytes), but asks the server to answer
+    /// `304 Not Modified` if the body still matches `validators`.
+    pub async fn get_conditional(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<Conditional, FetchError> {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = validators.etag.as_deref().and_then(header_value) {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = validators.last_modified.as_deref().and_then(header_value) {
+            headers.insert(IF_MODIFIED_SINCE, modified);
+        }
+
+        let (status, headers, body) = self.send(url, headers).await?;
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::NotModified);
+        }
+        let text = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Ok(Conditional::Modified {
+            status,
+            validators: Validators {
+                etag: text(ETAG),
+                last_modified: text(LAST_MODIFIED),
+            },
+            body,
+        })
+    }
+
+    async fn send(
+        &self,
+        url: &str,
+        headers: HeaderMap,
+    ) -> Result<(StatusCode, HeaderMap, Vec<u8>), FetchError> {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_token(url).await;
+
+            let request = self.client.get(url).headers(headers.clone());
+            let (error, retry_after) = match request.send().await {
+                Ok(response)
+                    if response.status().is_success()
+                        || response.status() == StatusCode::NOT_MODIFIED =>
+                {
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    match response.bytes().await {
+                        Ok(bytes) => return Ok((status, headers, bytes.to_vec())),
+                        Err(e) => (classify_network_error(url, e), None),
+                    }
+                }
+                Ok(response) => {
+                    let retry_after = parse_retry_after(&response);
+                    (classify_status(response.status()), retry_after)
+                }
+                Err(e) => (classify_network_error(url, e), None),
+            };
+
+            if !error.is_retryable() || attempt >= self.config.max_retries {
+                return Err(error);
+            }
+
+            let delay = retry_after
+                .map(|d| d.min(self.config.max_backoff))
+                .unwrap_or_else(|| self.backoff(attempt));
+            warn!(
+                "Attempt {} for {} failed ({}), retrying in {:?}",
+                attempt + 1,
+                url,
+                error,
+                delay
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Exponential backoff capped at `max_backoff`, randomly shortened by up
+    /// to half so that concurrent retries don't line up.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .config
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_backoff);
+        let jitter = rand::thread_rng().gen_range(0.5..=1.0);
+        ceiling.mul_f64(jitter)
+    }
+
+    async fn wait_for_token(&self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().await;
+                let bucket = buckets.entry(host.clone()).or_insert_with(|| {
+                    TokenBucket::new(self.config.burst, self.config.requests_per_second)
+                });
+                bucket.try_take(Instant::now())
+            };
+            match wait {
+                None => return,
+                Some(wait) => sleep(wait).await,
+            }
+        }
+    }
+}
+
+/// What a server said identifies a response body, for conditional requests.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Conditional {
+    NotModified,
+    Modified {
+        status: StatusCode,
+        body: Vec<u8>,
+        validators: Validators,
+    },
+}
+
+fn header_value(value: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(value).ok()
+}
+
+fn classify_status(status: StatusCode) -> FetchError {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        FetchError::Throttled(status)
+    } else if status.is_server_error() {
+        FetchError::UnexpectedStatusCode(status)
+    } else {
+        FetchError::PermanentFailure(status)
+    }
+}
+
+fn classify_network_error(url: &str, error: reqwest::Error) -> FetchError {
+    if error.is_timeout() {
+        FetchError::Timeout(url.to_string())
+    } else {
+        FetchError::Network(error)
+    }
+}
+
+fn parse_retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Classic token bucket: holds up to `capacity` tokens, refilled at
+/// `refill_per_sec`.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        TokenBucket {
+            capacity,
+            tokens: capacity,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long until
+    /// the next one is.
+    fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{test_client, MockResponse, MockServer};
+
+    #[test]
+    fn token_bucket_allows_burst_then_waits() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 1.0);
+        assert!(bucket.try_take(start).is_none());
+        assert!(bucket.try_take(start).is_none());
+
+        let wait = bucket.try_take(start).unwrap();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+
+        assert!(bucket.try_take(start + Duration::from_secs(1)).is_none());
+    }
+
+    #[test]
+    fn config_from_env_rejects_unusable_rates() {
+        let vars = |rate: &'static str| {
+            move |name: &str| (name == "NBA_REQUESTS_PER_SECOND").then(|| rate.to_string())
+        };
+        let config = ClientConfig::from_vars(vars("2.5")).unwrap();
+        assert_eq!(config.requests_per_second, 2.5);
+        for rate in ["0", "-1", "inf", "NaN", "fast"] {
+            let err = ClientConfig::from_vars(vars(rate)).unwrap_err();
+            assert!(err.contains("NBA_REQUESTS_PER_SECOND"), "{}", err);
+        }
+        assert!(ClientConfig::from_vars(|_| None).is_ok());
+    }
+
+    #[test]
+    fn config_from_env_rejects_zero_timeouts() {
+        for name in ["NBA_CONNECT_TIMEOUT_SECS", "NBA_READ_TIMEOUT_SECS"] {
+            let vars =
+                |secs: &'static str| move |var: &str| (var == name).then(|| secs.to_string());
+            assert!(ClientConfig::from_vars(vars("5")).is_ok());
+            let err = ClientConfig::from_vars(vars("0")).unwrap_err();
+            assert!(err.contains(name), "{}", err);
+        }
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let client = HttpClient::new(ClientConfig {
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            ..ClientConfig::default()
+        });
+        let first = client.backoff(0);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let third = client.backoff(2);
+        assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+        assert!(client.backoff(10) <= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn statuses_are_classified() {
+        assert!(matches!(
+            classify_status(StatusCode::TOO_MANY_REQUESTS),
+            FetchError::Throttled(_)
+        ));
+        assert!(classify_status(StatusCode::BAD_GATEWAY).is_retryable());
+        assert!(!classify_status(StatusCode::NOT_FOUND).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn throttled_requests_are_retried() {
+        let server = MockServer::start().await;
+        server.route(
+            "/stats/playerindex",
+            vec![MockResponse::status(429), MockResponse::ok("{}")],
+        );
+
+        let url = format!("{}/stats/playerindex", server.origin());
+        let body = test_client().get_bytes(&url).await.unwrap();
+        assert_eq!(body, b"{}");
+        assert_eq!(server.hits("/stats/playerindex"), 2);
+    }
+
+    #[tokio::test]
+    async fn server_errors_give_up_after_max_retries() {
+        let server = MockServer::start().await;
+        server.route("/flaky", vec![MockResponse::status(500)]);
+
+        let client = test_client();
+        let url = format!("{}/flaky", server.origin());
+        let error = client.get_bytes(&url).await.unwrap_err();
+        assert!(matches!(
+            error,
+            FetchError::UnexpectedStatusCode(StatusCode::INTERNAL_SERVER_ERROR)
+        ));
+        assert_eq!(
+            server.hits("/flaky"),
+            client.config().max_retries as usize + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let server = MockServer::start().await;
+        server.route("/missing", vec![MockResponse::status(404)]);
+
+        let url = format!("{}/missing", server.origin());
+        let error = test_client().get_bytes(&url).await.unwrap_err();
+        assert!(matches!(
+            error,
+            FetchError::PermanentFailure(StatusCode::NOT_FOUND)
+        ));
+        assert_eq!(server.hits("/missing"), 1);
+    }
+
+    #[tokio::test]
+    async fn slow_responses_time_out() {
+        let server = MockServer::start().await;
+        server.route(
+            "/slow",
+            vec![MockResponse::ok("{}").delayed(Duration::from_secs(2))],
+        );
+
+        let url = format!("{}/slow", server.origin());
+        let error = test_client().get_bytes(&url).await.unwrap_err();
+        assert!(matches!(error, FetchError::Timeout(_)), "{:?}", error);
+        assert_eq!(server.hits("/slow"), 3);
+    }
+
+    #[tokio::test]
+    async fn truncated_bodies_are_retried() {
+        let server = MockServer::start().await;
+        server.route(
+            "/cut",
+            vec![
+                MockResponse::ok("{\"resultSets\":[]}").truncated(),
+                MockResponse::ok("{\"resultSets\":[]}"),
+            ],
+        );
+
+        let url = format!("{}/cut", server.origin());
+        let body = test_client().get_bytes(&url).await.unwrap();
+        assert_eq!(body, b"{\"resultSets\":[]}");
+        assert_eq!(server.hits("/cut"), 2);
+    }
+}
@@ -8,4 +8,44 @@ pub enum FetchError {
     #[error("UTF-8 error: {0}")] Utf8(#[from] std::string::FromUtf8Error),
     #[error("Unexpected status code: {0}")] UnexpectedStatusCode(reqwest::StatusCode),
     #[error("Could not write record to csv: {0}")] CouldNotWriteRecord(#[from] csv::Error),
+    #[error("Request timed out: {0}")] Timeout(String),
+    #[error("Throttled by server, status code: {0}")] Throttled(reqwest::StatusCode),
+    #[error("Permanent failure, status code: {0}")] PermanentFailure(reqwest::StatusCode),
+    #[error("No fixture at {0}")] MissingFixture(std::path::PathBuf),
+    #[error("{endpoint:?} response has nothing usable at {pointer}")] UnexpectedShape { endpoint: crate::Endpoint, pointer: String },
+    #[error("{0:?} needs a game id")] MissingGameId(crate::Endpoint),
+    #[error("{0:?} is not a box score endpoint")] NotABoxScore(crate::Endpoint),
+    #[error("{0:?} is not a tracking measure")] NotATrackingMeasure(crate::Endpoint),
+    #[error("{0:?} is not a hustle endpoint")] NotAHustleEndpoint(crate::Endpoint),
+    #[error("{0:?} is not an odds endpoint")] NotAnOddsEndpoint(crate::Endpoint),
+    #[error("Requests failed for {} teams: {}", .0.len(), .0.join("; "))] TeamsFailed(Vec<String>),
+    #[error("Fetcher panicked: {0}")] Panicked(String),
+    #[error("Response has no result set {0}")] MissingResultSet(String),
+    #[error("Result set {result_set} is missing columns {missing:?}")] MissingColumns { result_set: String, missing: Vec<String> },
+}
+
+impl FetchError {
+    /// The HTTP status the server answered with, if it answered.
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            FetchError::UnexpectedStatusCode(status)
+            | FetchError::Throttled(status)
+            | FetchError::PermanentFailure(status) => Some(*status),
+            FetchError::Network(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// Whether trying the same request again might succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FetchError::Timeout(_) | FetchError::Throttled(_) => true,
+            FetchError::UnexpectedStatusCode(status) => status.is_server_error(),
+            // A body cut off mid-stream surfaces as a decode error.
+            FetchError::Network(e) => {
+                e.is_connect() || e.is_request() || e.is_body() || e.is_decode()
+            }
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,211 @@
+use crate::{
+    error::FetchError,
+    fetch_data,
+    result_sets::{result_set_row, ResultSetRow, ResultSetsFetchedResponse},
+    Endpoint, FetchContext,
+};
+use csv::WriterBuilder;
+use serde::Serialize;
+use std::{fs::File, path::PathBuf};
+
+result_set_row! {
+    /// One player in one game from `leaguegamelog` with `PlayerOrTeam=P`.
+    pub struct PlayerGameLogRow {
+        "SEASON_ID" => season_id: String,
+        "PLAYER_ID" => player_id: i64,
+        "PLAYER_NAME" => player_name: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "TEAM_NAME" => team_name: String,
+        "GAME_ID" => game_id: String,
+        "GAME_DATE" => game_date: String,
+        "MATCHUP" => matchup: String,
+        "WL" => wl: Option<String>,
+        "MIN" => min: i64,
+        "FGM" => fgm: i64,
+        "FGA" => fga: i64,
+        "FG_PCT" => fg_pct: Option<f64>,
+        "FG3M" => fg3m: i64,
+        "FG3A" => fg3a: i64,
+        "FG3_PCT" => fg3_pct: Option<f64>,
+        "FTM" => ftm: i64,
+        "FTA" => fta: i64,
+        "FT_PCT" => ft_pct: Option<f64>,
+        "OREB" => oreb: i64,
+        "DREB" => dreb: i64,
+        "REB" => reb: i64,
+        "AST" => ast: i64,
+        "STL" => stl: i64,
+        "BLK" => blk: i64,
+        "TOV" => tov: i64,
+        "PF" => pf: i64,
+        "PTS" => pts: i64,
+        "PLUS_MINUS" => plus_minus: i64,
+        "FANTASY_PTS" => fantasy_pts: f64,
+        "VIDEO_AVAILABLE" => video_available: i64,
+    }
+}
+
+result_set_row! {
+    /// One team in one game from `leaguegamelog` with `PlayerOrTeam=T`.
+    pub struct TeamGameLogRow {
+        "SEASON_ID" => season_id: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "TEAM_NAME" => team_name: String,
+        "GAME_ID" => game_id: String,
+        "GAME_DATE" => game_date: String,
+        "MATCHUP" => matchup: String,
+        "WL" => wl: Option<String>,
+        "MIN" => min: i64,
+        "FGM" => fgm: i64,
+        "FGA" => fga: i64,
+        "FG_PCT" => fg_pct: Option<f64>,
+        "FG3M" => fg3m: i64,
+        "FG3A" => fg3a: i64,
+        "FG3_PCT" => fg3_pct: Option<f64>,
+        "FTM" => ftm: i64,
+        "FTA" => fta: i64,
+        "FT_PCT" => ft_pct: Option<f64>,
+        "OREB" => oreb: i64,
+        "DREB" => dreb: i64,
+        "REB" => reb: i64,
+        "AST" => ast: i64,
+        "STL" => stl: i64,
+        "BLK" => blk: i64,
+        "TOV" => tov: i64,
+        "PF" => pf: i64,
+        "PTS" => pts: i64,
+        "PLUS_MINUS" => plus_minus: i64,
+        "VIDEO_AVAILABLE" => video_available: i64,
+    }
+}
+
+/// Columns the prepared game logs add after the endpoint's own, parsed
+/// from `MATCHUP`.
+const MATCHUP_COLUMNS: [&str; 2] = ["HOME_AWAY", "OPPONENT_ABBREVIATION"];
+
+/// Where a team played and against whom.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Matchup {
+    /// `H` or `A`.
+    pub home_away: &'static str,
+    pub opponent: String,
+}
+
+impl Matchup {
+    /// Parses `DET vs. HOU` (at home) or `HOU @ DET` (away).
+    pub fn parse(matchup: &str) -> Option<Self> {
+        let (home_away, opponent) = if let Some((_, opponent)) = matchup.split_once(" vs. ") {
+            ("H", opponent)
+        } else {
+            ("A", matchup.split_once(" @ ")?.1)
+        };
+        Some(Matchup {
+            home_away,
+            opponent: opponent.trim().to_string(),
+        })
+    }
+}
+
+pub async fn fetch_player_game_logs(ctx: &FetchContext) -> Result<(), FetchError> {
+    fetch_game_logs::<PlayerGameLogRow>(ctx, Endpoint::PlayerGameLogs, |row| &row.matchup).await
+}
+
+pub async fn fetch_team_game_logs(ctx: &FetchContext) -> Result<(), FetchError> {
+    fetch_game_logs::<TeamGameLogRow>(ctx, Endpoint::TeamGameLogs, |row| &row.matchup).await
+}
+
+async fn fetch_game_logs<T: ResultSetRow>(
+    ctx: &FetchContext,
+    endpoint: Endpoint,
+    matchup: fn(&T) -> &str,
+) -> Result<(), FetchError> {
+    // API Endpoint
+    let url = endpoint.url(&ctx.query);
+    // File Name for future reference
+    let file_name = endpoint.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+    // Fetch Data
+    let data: ResultSetsFetchedResponse = fetch_data(ctx, endpoint, &url, file_name).await?;
+    let rows: Vec<T> = data.first_result_set()?.rows()?;
+    write_game_logs_to_csv(&rows, matchup, save_file_path)
+}
+
+/// Writes `rows` with [`MATCHUP_COLUMNS`] appended to each. A matchup that
+/// doesn't parse leaves those two cells empty.
+fn write_game_logs_to_csv<T: ResultSetRow>(
+    rows: &[T],
+    matchup: fn(&T) -> &str,
+    path: PathBuf,
+) -> Result<(), FetchError> {
+    let file = File::create(path)?;
+    // The header is written by hand: csv can't name the columns of a
+    // struct nested in a tuple.
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+    wtr.write_record(T::COLUMNS.iter().chain(MATCHUP_COLUMNS.iter()))?;
+    for row in rows {
+        let parsed = Matchup::parse(matchup(row));
+        let home_away = parsed.as_ref().map_or("", |m| m.home_away);
+        let opponent = parsed.as_ref().map_or("", |m| m.opponent.as_str());
+        wtr.serialize((row, home_away, opponent))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport, OutputDirs};
+    use std::{env, fs, sync::Arc};
+
+    #[test]
+    fn matchup_gives_venue_and_opponent() {
+        assert_eq!(
+            Matchup::parse("DET vs. HOU"),
+            Some(Matchup {
+                home_away: "H",
+                opponent: "HOU".to_string()
+            })
+        );
+        assert_eq!(Matchup::parse("HOU @ DET").unwrap().home_away, "A");
+        assert_eq!(Matchup::parse("HOU @ DET").unwrap().opponent, "DET");
+        assert_eq!(Matchup::parse("HOU"), None);
+    }
+
+    #[tokio::test]
+    async fn game_logs_are_written_with_matchup_columns() {
+        let dir = env::temp_dir().join("nba_game_logs");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_player_game_logs(&ctx).await.unwrap();
+        fetch_team_game_logs(&ctx).await.unwrap();
+
+        let mut players =
+            csv::Reader::from_path(dirs.prepared.join("fetched_PlayerGameLogs_data.csv")).unwrap();
+        let headers = players.headers().unwrap().clone();
+        assert_eq!(
+            headers.len(),
+            PlayerGameLogRow::COLUMNS.len() + MATCHUP_COLUMNS.len()
+        );
+        let cade = players.records().next().unwrap().unwrap();
+        assert_eq!(&cade[2], "Cade Cunningham");
+        assert_eq!(&cade[headers.len() - 2], "H");
+        assert_eq!(&cade[headers.len() - 1], "HOU");
+
+        let teams =
+            fs::read_to_string(dirs.prepared.join("fetched_TeamGameLogs_data.csv")).unwrap();
+        let lines: Vec<&str> = teams.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].ends_with("VIDEO_AVAILABLE,HOME_AWAY,OPPONENT_ABBREVIATION"));
+        assert!(lines[2].contains("HOU @ DET") && lines[2].ends_with(",A,DET"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,54 +1,307 @@
-use crate::{error::FetchError, fetch_data, Endpoint};
-use std::{collections::HashSet, fs::File, path::PathBuf};
-
+use crate::{error::FetchError, fetch_data, Endpoint, FetchContext};
 use csv::Writer;
-use serde_json::Value;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::Infallible, fmt, fs::File, str::FromStr};
+
+/// One row of Rotowire's injury table as it is sent. Every value is a
+/// string; `rDate` is HTML and often paywalled.
+#[derive(Deserialize, Debug)]
+struct RotowireInjury {
+    #[serde(rename = "ID")]
+    id: String,
+    player: String,
+    team: String,
+    #[serde(default)]
+    position: String,
+    #[serde(default)]
+    injury: String,
+    status: String,
+    #[serde(rename = "rDate", default)]
+    return_date: Option<String>,
+}
+
+/// How likely an injured player is to miss the next game, most likely first.
+/// A status Rotowire sends that isn't one of these, e.g. `Suspended`, is
+/// kept as written in `Other`, last.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InjuryStatus {
+    OutForSeason,
+    Out,
+    Doubtful,
+    Questionable,
+    GameTimeDecision,
+    DayToDay,
+    Probable,
+    Other(String),
+}
 
-type InjuryReportFetchedResponse = Vec<Value>;
+impl InjuryStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            InjuryStatus::OutForSeason => "Out For Season",
+            InjuryStatus::Out => "Out",
+            InjuryStatus::Doubtful => "Doubtful",
+            InjuryStatus::Questionable => "Questionable",
+            InjuryStatus::GameTimeDecision => "GTD",
+            InjuryStatus::DayToDay => "Day-To-Day",
+            InjuryStatus::Probable => "Probable",
+            InjuryStatus::Other(status) => status,
+        }
+    }
 
-fn json_array_to_csv(data: Vec<Value>, file_path: PathBuf) -> Result<(), FetchError> {
-    let mut headers = HashSet::new();
-    for obj in &data {
-        if let Value::Object(map) = obj {
-            for key in map.keys() {
-                headers.insert(key.clone());
+    /// Whether the player won't play, as opposed to being in doubt.
+    pub fn is_out(&self) -> bool {
+        matches!(self, InjuryStatus::OutForSeason | InjuryStatus::Out)
+    }
+}
+
+impl fmt::Display for InjuryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for InjuryStatus {
+    type Err = Infallible;
+
+    /// Accepts Rotowire's spellings, e.g. `Game Time Decision`, as well as
+    /// the short ones written out. Anything else is `Other`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "out for season" => Ok(InjuryStatus::OutForSeason),
+            "out" => Ok(InjuryStatus::Out),
+            "doubtful" => Ok(InjuryStatus::Doubtful),
+            "questionable" => Ok(InjuryStatus::Questionable),
+            "gtd" | "game time decision" | "game-time decision" => {
+                Ok(InjuryStatus::GameTimeDecision)
             }
+            "day-to-day" | "day to day" | "dtd" => Ok(InjuryStatus::DayToDay),
+            "probable" => Ok(InjuryStatus::Probable),
+            _ => Ok(InjuryStatus::Other(s.trim().to_string())),
         }
     }
+}
 
-    
-    let file = File::create(file_path)?;
-    let mut wtr = Writer::from_writer(file);
-    
-    let headers: Vec<&str> = headers.iter().map(AsRef::as_ref).collect();
-    wtr.write_record(&headers)?;
-
-    for obj in data {
-        if let Value::Object(map) = obj {
-            let row: Vec<String> = headers
-                .iter()
-                .map(|key| {
-                    map.get(*key)
-                        .map_or("".to_string(), |v| v.as_str().unwrap().to_string())
-                })
-                .collect();
-            wtr.write_record(&row)?;
+/// Written as [`InjuryStatus::as_str`], so the CSV and JSON read the same.
+impl Serialize for InjuryStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for InjuryStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let status = String::deserialize(deserializer)?;
+        let Ok(status) = status.parse();
+        Ok(status)
+    }
+}
+
+/// One injured player, one row of `fetched_InjuryReport_data.csv`.
+/// `rotowire_id` is Rotowire's player ID and `return_date` its estimate as
+/// written, e.g. `Nov 20`, left empty when unknown or paywalled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InjuryEntry {
+    pub rotowire_id: String,
+    pub player: String,
+    pub team: String,
+    pub position: String,
+    pub injury: String,
+    pub status: InjuryStatus,
+    pub return_date: Option<String>,
+}
+
+impl From<RotowireInjury> for InjuryEntry {
+    fn from(row: RotowireInjury) -> Self {
+        let Ok(status) = row.status.parse();
+        InjuryEntry {
+            status,
+            return_date: row.return_date.as_deref().and_then(return_date),
+            rotowire_id: row.id,
+            player: row.player,
+            team: row.team,
+            position: row.position,
+            injury: row.injury,
         }
     }
-    wtr.flush()?;
-    Ok(())
 }
 
-pub async fn fetch_injury_report() -> Result<(), FetchError> {
+/// The text of an `rDate` cell, without its markup. `None` if it doesn't
+/// give a date.
+fn return_date(html: &str) -> Option<String> {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("Subscribers Only") {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+/// Types every row, in the order Rotowire lists them.
+fn injury_entries(rows: Vec<RotowireInjury>) -> Vec<InjuryEntry> {
+    rows.into_iter().map(InjuryEntry::from).collect()
+}
+
+pub async fn fetch_injury_report(ctx: &FetchContext) -> Result<(), FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::InjuryReport.url();
+    let endpoint = Endpoint::InjuryReport.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::InjuryReport.file_name();
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
-    save_file_path.set_extension("csv");
     // Fetch Data
-    let data: InjuryReportFetchedResponse = fetch_data(endpoint, file_name).await?;
-    json_array_to_csv(data, save_file_path)?;
+    let data: Vec<RotowireInjury> =
+        fetch_data(ctx, Endpoint::InjuryReport, &endpoint, file_name).await?;
+    let entries = injury_entries(data);
+
+    let save_file_path = ctx.dirs.prepared.join(file_name);
+    let mut wtr = Writer::from_writer(File::create(save_file_path.with_extension("csv"))?);
+    for entry in &entries {
+        wtr.serialize(entry)?;
+    }
+    wtr.flush()?;
+
+    // The same entries as JSON, for readers that want the status typed.
+    let file = File::create(save_file_path.with_extension("json"))?;
+    serde_json::to_writer_pretty(file, &entries)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport, OutputDirs};
+    use std::{env, fs, sync::Arc};
+
+    fn seed_entries() -> Vec<InjuryEntry> {
+        let path = Endpoint::data_file_path()
+            .join("seed_data")
+            .join("injuryReport.json");
+        injury_entries(serde_json::from_slice(&fs::read(path).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn parses_the_seed_report() {
+        let entries = seed_entries();
+        assert_eq!(entries.len(), 101);
+        assert_eq!(
+            entries[0],
+            InjuryEntry {
+                rotowire_id: "5249".to_string(),
+                player: "Precious Achiuwa".to_string(),
+                team: "NYK".to_string(),
+                position: "C".to_string(),
+                injury: "Hamstring".to_string(),
+                status: InjuryStatus::Out,
+                return_date: None,
+            }
+        );
+        assert_eq!(entries[1].status, InjuryStatus::GameTimeDecision);
+
+        let count = |status| entries.iter().filter(|e| e.status == status).count();
+        assert_eq!(count(InjuryStatus::Out), 65);
+        assert_eq!(count(InjuryStatus::GameTimeDecision), 31);
+        assert_eq!(count(InjuryStatus::OutForSeason), 5);
+        // Every return date in the seed is paywalled.
+        assert!(entries.iter().all(|e| e.return_date.is_none()));
+    }
+
+    #[test]
+    fn statuses_and_return_dates_are_read_leniently() {
+        for (text, status) in [
+            ("Out", InjuryStatus::Out),
+            ("Out For Season", InjuryStatus::OutForSeason),
+            ("doubtful", InjuryStatus::Doubtful),
+            ("Questionable", InjuryStatus::Questionable),
+            ("Game Time Decision", InjuryStatus::GameTimeDecision),
+            ("GTD", InjuryStatus::GameTimeDecision),
+            ("Day-To-Day", InjuryStatus::DayToDay),
+            (" Probable ", InjuryStatus::Probable),
+        ] {
+            assert_eq!(text.parse::<InjuryStatus>(), Ok(status));
+        }
+        assert_eq!(
+            " Suspended".parse::<InjuryStatus>(),
+            Ok(InjuryStatus::Other("Suspended".to_string()))
+        );
+
+        assert_eq!(return_date("<i>Subscribers Only</i>"), None);
+        assert_eq!(return_date("<b>Nov 20</b>"), Some("Nov 20".to_string()));
+        assert_eq!(return_date(""), None);
+
+        let rows: Vec<RotowireInjury> = serde_json::from_str(
+            r#"[
+                {"ID": "1", "player": "A", "team": "BOS", "status": "Suspended"},
+                {"ID": "2", "player": "B", "team": "BOS", "status": "Probable", "rDate": "Nov 20"}
+            ]"#,
+        )
+        .unwrap();
+        let entries = injury_entries(rows);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].status,
+            InjuryStatus::Other("Suspended".to_string())
+        );
+        assert!(!entries[0].status.is_out());
+        assert_eq!(entries[1].return_date.as_deref(), Some("Nov 20"));
+
+        // Unknown statuses are written as sent and read back the same.
+        let json = serde_json::to_string(&entries[0]).unwrap();
+        assert!(json.contains(r#""status":"Suspended""#));
+        let entry: InjuryEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(entry, entries[0]);
+        let gtd: InjuryEntry = serde_json::from_str(&json.replace("Suspended", "GTD")).unwrap();
+        assert_eq!(gtd.status, InjuryStatus::GameTimeDecision);
+    }
+
+    #[tokio::test]
+    async fn writes_the_same_columns_every_run() {
+        let dir = env::temp_dir().join("nba_injury_report");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        let csv_path = dirs.prepared.join("fetched_InjuryReport_data.csv");
+        let mut previous = None;
+        for _ in 0..2 {
+            fetch_injury_report(&ctx).await.unwrap();
+            let csv = fs::read(&csv_path).unwrap();
+            if let Some(previous) = previous.replace(csv.clone()) {
+                assert_eq!(previous, csv);
+            }
+        }
+
+        let mut reader = csv::Reader::from_path(&csv_path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec![
+                "rotowire_id",
+                "player",
+                "team",
+                "position",
+                "injury",
+                "status",
+                "return_date"
+            ]
+        );
+        let from_csv: Vec<InjuryEntry> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(from_csv, seed_entries());
+        assert_eq!(from_csv[1].status.to_string(), "GTD");
+
+        let json = fs::read(csv_path.with_extension("json")).unwrap();
+        let from_json: Vec<InjuryEntry> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(from_json, from_csv);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,31 +1,67 @@
+pub mod backfill;
 pub mod bet_ml_odds;
+pub mod box_scores;
+pub mod cache;
+pub mod client;
 pub mod error;
+pub mod game_logs;
 pub mod injury_report;
+pub mod lineups;
+pub mod live;
+pub mod manifest;
+pub mod market_odds;
+#[cfg(test)]
+mod mock_server;
+pub mod next_build;
+pub mod odds_history;
 pub mod opponent_shooting_general;
+pub mod per_game;
+pub mod pipeline;
+pub mod play_by_play;
 pub mod player_general_averages;
 pub mod player_index;
+pub mod query;
 pub mod result_sets;
+pub mod schema;
 pub mod season_schedule;
+pub mod shot_chart;
+pub mod snapshot;
+pub mod standings;
 pub mod teams_general_advanced;
 pub mod teams_general_opponent;
-// pub mod todays_scoreboard;
+pub mod todays_scoreboard;
+pub mod tracking;
+pub mod transport;
 
 use crate::error::FetchError;
 use bet_ml_odds::fetch_bet_ml_odds;
+use box_scores::fetch_box_score;
+use chrono::Utc;
 use flate2::read::GzDecoder;
+use game_logs::{fetch_player_game_logs, fetch_team_game_logs};
 use injury_report::fetch_injury_report;
+use lineups::{fetch_lineups, fetch_player_on_off, player_on_off_raw_path};
+use manifest::{FetchManifest, FetchedRequest, RequestLog};
+use market_odds::{fetch_market_odds, odds_page_url, OddsMarket, OddsScope};
+use next_build::{fetch_odds_build_id, BuildIdCache, FALLBACK_BUILD_ID, ODDS_LANDING_URL};
 use opponent_shooting_general::fetch_opponent_shooting_general;
+use pipeline::{fetch_concurrently, FetchOutcome, FetchReport};
+use play_by_play::fetch_play_by_play;
 use player_general_averages::fetch_player_general_averages;
 use player_index::fetch_player_index;
-use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Client,
-};
+use query::{build_url, MeasureType, PtMeasureType, Season, SeasonType, StatsQuery, TEAM_IDS};
+use reqwest::header::{HeaderMap, HeaderValue};
+use schema::SchemaRegistry;
 use season_schedule::fetch_season_schedule;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use shot_chart::fetch_shot_chart;
+use snapshot::Snapshot;
+use standings::fetch_standings;
 use std::io::Read;
 use std::io::{self, Write};
 use std::path::Path;
+use std::sync::Arc;
 use std::{
     env,
     fs::{self, File},
@@ -33,9 +69,12 @@ use std::{
 };
 use teams_general_advanced::fetch_teams_general_advanced;
 use teams_general_opponent::fetch_teams_general_opponent;
+use todays_scoreboard::fetch_todays_scoreboard;
 use tracing::{debug, error, info};
+use tracking::{fetch_hustle, fetch_tracking};
+use transport::{http_transport, Transport};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Endpoint {
     SeasonSchedule,
     TodaysScoreboard,
@@ -44,31 +83,423 @@ pub enum Endpoint {
     TeamsGeneralAdvanced,
     OpponentShootingGeneral,
     TeamsGeneralOpponent,
+    PlayerGameLogs,
+    TeamGameLogs,
+    Lineups,
+    PlayerOnOff,
+    Standings,
+    PlayerTracking(PtMeasureType),
+    TeamTracking(PtMeasureType),
+    PlayerHustle,
+    TeamHustle,
+    ShotChart,
     InjuryReport,
     Bet365Odds,
+    Odds(OddsMarket, OddsScope),
+    /// The odds site's HTML page, fetched only for the build ID the odds
+    /// data URLs need.
+    OddsLandingPage,
+    BoxScoreTraditional,
+    BoxScoreAdvanced,
+    BoxScoreFourFactors,
+    BoxScoreUsage,
+    PlayByPlay,
 }
 
 impl Endpoint {
-    pub fn url(&self) -> &'static str {
+    /// The URL `query` asks this endpoint for. The odds pages live under a
+    /// build ID that is only known once fetched, so theirs name
+    /// [`FALLBACK_BUILD_ID`]; what was requested is in the manifest.
+    pub fn url(&self, query: &StatsQuery) -> String {
+        let season = query.season().to_string();
+        let season_type = query.season_type().as_str();
+        let per_mode = query.per_mode().as_str();
+        let last_n_games = query.last_n_games();
+        let date_from = query.date_from();
+        let date_to = query.date_to();
+        let location = query.location();
+        let outcome = query.outcome();
+        let opponent_team_id = query.opponent_team_id();
+        let team_id = query.team_id().unwrap_or(0).to_string();
+        let game_id = query.game_id().unwrap_or_default();
+        let measure_type =
+            query.measure_type_or(self.default_measure_type().unwrap_or(MeasureType::Base));
+
         match self {
-            Endpoint::SeasonSchedule =>
-                "https://cdn.nba.com/static/json/staticData/scheduleLeagueV2_51.json",
+            // The CDN only serves the current season; older schedules come
+            // from the stats API in the same shape.
+            Endpoint::SeasonSchedule if query.season() == Season::current() =>
+                "https://cdn.nba.com/static/json/staticData/scheduleLeagueV2_51.json".to_string(),
+            Endpoint::SeasonSchedule => build_url(
+                "https://stats.nba.com/stats/scheduleleaguev2",
+                &[("LeagueID", "00"), ("Season", &season)],
+            ),
             Endpoint::TodaysScoreboard =>
-                "https://cdn.nba.com/static/json/liveData/scoreboard/todaysScoreboard_00.json",
-            Endpoint::PlayerIndex =>
-                "https://stats.nba.com/stats/playerindex?College=&Country=&DraftPick=&DraftRound=&DraftYear=&Height=&Historical=0&LeagueID=00&Season=2024-25&SeasonType=Regular%20Season&TeamID=0&Weight=",
-            Endpoint::PlayerGeneralAverages =>
-                "https://stats.nba.com/stats/leaguedashplayerstats?College=&Conference=&Country=&DateFrom=&DateTo=&Division=&DraftPick=&DraftYear=&GameScope=&GameSegment=&Height=&ISTRound=&LastNGames=0&LeagueID=00&Location=&MeasureType=Base&Month=0&OpponentTeamID=0&Outcome=&PORound=0&PaceAdjust=N&PerMode=PerGame&Period=0&PlayerExperience=&PlayerPosition=&PlusMinus=N&Rank=N&Season=2024-25&SeasonSegment=&SeasonType=Regular%20Season&ShotClockRange=&StarterBench=&TeamID=0&VsConference=&VsDivision=&Weight=",
-            Endpoint::TeamsGeneralAdvanced =>
-                "https://stats.nba.com/stats/leaguedashteamstats?Conference=&DateFrom=&DateTo=&Division=&GameScope=&GameSegment=&Height=&ISTRound=&LastNGames=0&LeagueID=00&Location=&MeasureType=Advanced&Month=0&OpponentTeamID=0&Outcome=&PORound=0&PaceAdjust=N&PerMode=PerGame&Period=0&PlayerExperience=&PlayerPosition=&PlusMinus=N&Rank=N&Season=2024-25&SeasonSegment=&SeasonType=Regular%20Season&ShotClockRange=&StarterBench=&TeamID=0&TwoWay=0&VsConference=&VsDivision=",
-            Endpoint::OpponentShootingGeneral =>
-                "https://stats.nba.com/stats/leaguedashoppptshot?Conference=&DateFrom=&DateTo=&Division=&GameSegment=&GeneralRange=Overall&LastNGames=0&LeagueID=00&Location=&Month=0&OpponentTeamID=0&Outcome=&PORound=0&PerMode=PerGame&Period=0&PlayerExperience=&PlayerPosition=&Season=2024-25&SeasonSegment=&SeasonType=Regular%20Season&TeamID=0&VsConference=&VsDivision=",
-            Endpoint::TeamsGeneralOpponent =>
-                "https://stats.nba.com/stats/leaguedashteamstats?Conference=&DateFrom=&DateTo=&Division=&GameScope=&GameSegment=&Height=&ISTRound=&LastNGames=0&LeagueID=00&Location=&MeasureType=Base&Month=0&OpponentTeamID=0&Outcome=&PORound=0&PaceAdjust=N&PerMode=PerGame&Period=0&PlayerExperience=&PlayerPosition=&PlusMinus=N&Rank=N&Season=2024-25&SeasonSegment=&SeasonType=Regular%20Season&ShotClockRange=&StarterBench=&TeamID=0&TwoWay=0&VsConference=&VsDivision=",
+                "https://cdn.nba.com/static/json/liveData/scoreboard/todaysScoreboard_00.json".to_string(),
+            Endpoint::PlayerIndex => build_url(
+                "https://stats.nba.com/stats/playerindex",
+                &[
+                    ("College", ""),
+                    ("Country", ""),
+                    ("DraftPick", ""),
+                    ("DraftRound", ""),
+                    ("DraftYear", ""),
+                    ("Height", ""),
+                    ("Historical", "0"),
+                    ("LeagueID", "00"),
+                    ("Season", &season),
+                    ("SeasonType", season_type),
+                    ("TeamID", "0"),
+                    ("Weight", ""),
+                ],
+            ),
+            Endpoint::PlayerGeneralAverages => build_url(
+                "https://stats.nba.com/stats/leaguedashplayerstats",
+                &[
+                    ("College", ""),
+                    ("Conference", ""),
+                    ("Country", ""),
+                    ("DateFrom", &date_from),
+                    ("DateTo", &date_to),
+                    ("Division", ""),
+                    ("DraftPick", ""),
+                    ("DraftYear", ""),
+                    ("GameScope", ""),
+                    ("GameSegment", ""),
+                    ("Height", ""),
+                    ("ISTRound", ""),
+                    ("LastNGames", &last_n_games),
+                    ("LeagueID", "00"),
+                    ("Location", location),
+                    ("MeasureType", measure_type),
+                    ("Month", "0"),
+                    ("OpponentTeamID", &opponent_team_id),
+                    ("Outcome", outcome),
+                    ("PORound", "0"),
+                    ("PaceAdjust", "N"),
+                    ("PerMode", per_mode),
+                    ("Period", "0"),
+                    ("PlayerExperience", ""),
+                    ("PlayerPosition", ""),
+                    ("PlusMinus", "N"),
+                    ("Rank", "N"),
+                    ("Season", &season),
+                    ("SeasonSegment", ""),
+                    ("SeasonType", season_type),
+                    ("ShotClockRange", ""),
+                    ("StarterBench", ""),
+                    ("TeamID", "0"),
+                    ("VsConference", ""),
+                    ("VsDivision", ""),
+                    ("Weight", ""),
+                ],
+            ),
+            Endpoint::TeamsGeneralAdvanced | Endpoint::TeamsGeneralOpponent => build_url(
+                "https://stats.nba.com/stats/leaguedashteamstats",
+                &[
+                    ("Conference", ""),
+                    ("DateFrom", &date_from),
+                    ("DateTo", &date_to),
+                    ("Division", ""),
+                    ("GameScope", ""),
+                    ("GameSegment", ""),
+                    ("Height", ""),
+                    ("ISTRound", ""),
+                    ("LastNGames", &last_n_games),
+                    ("LeagueID", "00"),
+                    ("Location", location),
+                    ("MeasureType", measure_type),
+                    ("Month", "0"),
+                    ("OpponentTeamID", &opponent_team_id),
+                    ("Outcome", outcome),
+                    ("PORound", "0"),
+                    ("PaceAdjust", "N"),
+                    ("PerMode", per_mode),
+                    ("Period", "0"),
+                    ("PlayerExperience", ""),
+                    ("PlayerPosition", ""),
+                    ("PlusMinus", "N"),
+                    ("Rank", "N"),
+                    ("Season", &season),
+                    ("SeasonSegment", ""),
+                    ("SeasonType", season_type),
+                    ("ShotClockRange", ""),
+                    ("StarterBench", ""),
+                    ("TeamID", "0"),
+                    ("TwoWay", "0"),
+                    ("VsConference", ""),
+                    ("VsDivision", ""),
+                ],
+            ),
+            Endpoint::OpponentShootingGeneral => build_url(
+                "https://stats.nba.com/stats/leaguedashoppptshot",
+                &[
+                    ("Conference", ""),
+                    ("DateFrom", &date_from),
+                    ("DateTo", &date_to),
+                    ("Division", ""),
+                    ("GameSegment", ""),
+                    ("GeneralRange", "Overall"),
+                    ("LastNGames", &last_n_games),
+                    ("LeagueID", "00"),
+                    ("Location", location),
+                    ("Month", "0"),
+                    ("OpponentTeamID", &opponent_team_id),
+                    ("Outcome", outcome),
+                    ("PORound", "0"),
+                    ("PerMode", per_mode),
+                    ("Period", "0"),
+                    ("PlayerExperience", ""),
+                    ("PlayerPosition", ""),
+                    ("Season", &season),
+                    ("SeasonSegment", ""),
+                    ("SeasonType", season_type),
+                    ("TeamID", "0"),
+                    ("VsConference", ""),
+                    ("VsDivision", ""),
+                ],
+            ),
+            Endpoint::PlayerGameLogs | Endpoint::TeamGameLogs => {
+                let player_or_team = match self {
+                    Endpoint::PlayerGameLogs => "P",
+                    _ => "T",
+                };
+                build_url(
+                    "https://stats.nba.com/stats/leaguegamelog",
+                    &[
+                        ("Counter", "1000"),
+                        ("DateFrom", &date_from),
+                        ("DateTo", &date_to),
+                        ("Direction", "DESC"),
+                        ("ISTRound", ""),
+                        ("LeagueID", "00"),
+                        ("PlayerOrTeam", player_or_team),
+                        ("Season", &season),
+                        ("SeasonType", season_type),
+                        ("Sorter", "DATE"),
+                    ],
+                )
+            }
+            Endpoint::Lineups => build_url(
+                "https://stats.nba.com/stats/leaguedashlineups",
+                &[
+                    ("Conference", ""),
+                    ("DateFrom", &date_from),
+                    ("DateTo", &date_to),
+                    ("Division", ""),
+                    ("GameSegment", ""),
+                    ("GroupQuantity", "5"),
+                    ("ISTRound", ""),
+                    ("LastNGames", &last_n_games),
+                    ("LeagueID", "00"),
+                    ("Location", location),
+                    ("MeasureType", measure_type),
+                    ("Month", "0"),
+                    ("OpponentTeamID", &opponent_team_id),
+                    ("Outcome", outcome),
+                    ("PORound", "0"),
+                    ("PaceAdjust", "N"),
+                    ("PerMode", per_mode),
+                    ("Period", "0"),
+                    ("PlusMinus", "N"),
+                    ("Rank", "N"),
+                    ("Season", &season),
+                    ("SeasonSegment", ""),
+                    ("SeasonType", season_type),
+                    ("ShotClockRange", ""),
+                    ("TeamID", "0"),
+                    ("VsConference", ""),
+                    ("VsDivision", ""),
+                ],
+            ),
+            Endpoint::PlayerOnOff => build_url(
+                "https://stats.nba.com/stats/teamplayeronoffsummary",
+                &[
+                    ("DateFrom", &date_from),
+                    ("DateTo", &date_to),
+                    ("GameSegment", ""),
+                    ("LastNGames", &last_n_games),
+                    ("LeagueID", "00"),
+                    ("Location", location),
+                    ("MeasureType", measure_type),
+                    ("Month", "0"),
+                    ("OpponentTeamID", &opponent_team_id),
+                    ("Outcome", outcome),
+                    ("PORound", "0"),
+                    ("PaceAdjust", "N"),
+                    ("PerMode", per_mode),
+                    ("Period", "0"),
+                    ("PlusMinus", "N"),
+                    ("Rank", "N"),
+                    ("Season", &season),
+                    ("SeasonSegment", ""),
+                    ("SeasonType", season_type),
+                    ("TeamID", &team_id),
+                    ("VsConference", ""),
+                    ("VsDivision", ""),
+                ],
+            ),
+            Endpoint::Standings => build_url(
+                "https://stats.nba.com/stats/leaguestandingsv3",
+                &[
+                    ("LeagueID", "00"),
+                    ("Season", &season),
+                    ("SeasonType", season_type),
+                    ("SeasonYear", ""),
+                ],
+            ),
+            Endpoint::PlayerTracking(pt_measure_type) | Endpoint::TeamTracking(pt_measure_type) => {
+                let player_or_team = match self {
+                    Endpoint::PlayerTracking(_) => "Player",
+                    _ => "Team",
+                };
+                build_url(
+                    "https://stats.nba.com/stats/leaguedashptstats",
+                    &[
+                        ("College", ""),
+                        ("Conference", ""),
+                        ("Country", ""),
+                        ("DateFrom", &date_from),
+                        ("DateTo", &date_to),
+                        ("Division", ""),
+                        ("DraftPick", ""),
+                        ("DraftYear", ""),
+                        ("GameScope", ""),
+                        ("Height", ""),
+                        ("LastNGames", &last_n_games),
+                        ("LeagueID", "00"),
+                        ("Location", location),
+                        ("Month", "0"),
+                        ("OpponentTeamID", &opponent_team_id),
+                        ("Outcome", outcome),
+                        ("PORound", "0"),
+                        ("PerMode", per_mode),
+                        ("PlayerExperience", ""),
+                        ("PlayerOrTeam", player_or_team),
+                        ("PlayerPosition", ""),
+                        ("PtMeasureType", pt_measure_type.as_str()),
+                        ("Season", &season),
+                        ("SeasonSegment", ""),
+                        ("SeasonType", season_type),
+                        ("StarterBench", ""),
+                        ("TeamID", "0"),
+                        ("VsConference", ""),
+                        ("VsDivision", ""),
+                        ("Weight", ""),
+                    ],
+                )
+            }
+            Endpoint::PlayerHustle | Endpoint::TeamHustle => {
+                let base = match self {
+                    Endpoint::PlayerHustle => "https://stats.nba.com/stats/leaguehustlestatsplayer",
+                    _ => "https://stats.nba.com/stats/leaguehustlestatsteam",
+                };
+                build_url(
+                    base,
+                    &[
+                        ("College", ""),
+                        ("Conference", ""),
+                        ("Country", ""),
+                        ("DateFrom", &date_from),
+                        ("DateTo", &date_to),
+                        ("Division", ""),
+                        ("DraftPick", ""),
+                        ("DraftYear", ""),
+                        ("GameScope", ""),
+                        ("Height", ""),
+                        ("LastNGames", &last_n_games),
+                        ("LeagueID", "00"),
+                        ("Location", location),
+                        ("Month", "0"),
+                        ("OpponentTeamID", &opponent_team_id),
+                        ("Outcome", outcome),
+                        ("PORound", "0"),
+                        ("PerMode", per_mode),
+                        ("PlayerExperience", ""),
+                        ("PlayerPosition", ""),
+                        ("Season", &season),
+                        ("SeasonSegment", ""),
+                        ("SeasonType", season_type),
+                        ("TeamID", "0"),
+                        ("VsConference", ""),
+                        ("VsDivision", ""),
+                        ("Weight", ""),
+                    ],
+                )
+            }
+            Endpoint::ShotChart => build_url(
+                "https://stats.nba.com/stats/shotchartdetail",
+                &[
+                    ("AheadBehind", ""),
+                    ("ClutchTime", ""),
+                    ("ContextFilter", ""),
+                    ("ContextMeasure", "FGA"),
+                    ("DateFrom", &date_from),
+                    ("DateTo", &date_to),
+                    ("EndPeriod", ""),
+                    ("EndRange", ""),
+                    ("GameID", game_id),
+                    ("GameSegment", ""),
+                    ("LastNGames", &last_n_games),
+                    ("LeagueID", "00"),
+                    ("Location", location),
+                    ("Month", "0"),
+                    ("OpponentTeamID", &opponent_team_id),
+                    ("Outcome", outcome),
+                    ("PORound", "0"),
+                    ("Period", "0"),
+                    ("PlayerID", "0"),
+                    ("PlayerPosition", ""),
+                    ("RangeType", ""),
+                    ("RookieYear", ""),
+                    ("Season", &season),
+                    ("SeasonSegment", ""),
+                    ("SeasonType", season_type),
+                    ("StartPeriod", ""),
+                    ("StartRange", ""),
+                    ("TeamID", &team_id),
+                    ("VsConference", ""),
+                    ("VsDivision", ""),
+                ],
+            ),
             Endpoint::InjuryReport =>
-                "https://www.rotowire.com/basketball/tables/injury-report.php?team=ALL&pos=ALL",
-            Endpoint::Bet365Odds =>
-                "https://www.sportsbookreview.com/_next/data/z65WyQ2UMs83FwnSdRynY/betting-odds/nba-basketball/money-line/full-game.json?league=nba-basketball&oddsType=money-line&oddsScope=full-game",
+                "https://www.rotowire.com/basketball/tables/injury-report.php?team=ALL&pos=ALL".to_string(),
+            // Fetches put the build resolved through `ctx.odds_build` in place
+            // of the fallback, and log that URL for the manifest.
+            Endpoint::Bet365Odds => odds_page_url(
+                FALLBACK_BUILD_ID,
+                OddsMarket::MoneyLine,
+                OddsScope::FullGame,
+            ),
+            Endpoint::Odds(market, scope) => odds_page_url(FALLBACK_BUILD_ID, *market, *scope),
+            Endpoint::OddsLandingPage => ODDS_LANDING_URL.to_string(),
+            Endpoint::PlayByPlay => format!(
+                "https://cdn.nba.com/static/json/liveData/playbyplay/playbyplay_{}.json",
+                game_id
+            ),
+            Endpoint::BoxScoreTraditional
+            | Endpoint::BoxScoreAdvanced
+            | Endpoint::BoxScoreFourFactors
+            | Endpoint::BoxScoreUsage => {
+                let path = match self {
+                    Endpoint::BoxScoreTraditional => "boxscoretraditionalv3",
+                    Endpoint::BoxScoreAdvanced => "boxscoreadvancedv3",
+                    Endpoint::BoxScoreFourFactors => "boxscorefourfactorsv3",
+                    _ => "boxscoreusagev3",
+                };
+                build_url(
+                    &format!("https://stats.nba.com/stats/{}", path),
+                    &[
+                        ("EndPeriod", "0"),
+                        ("EndRange", "0"),
+                        ("GameID", game_id),
+                        ("LeagueID", "00"),
+                        ("RangeType", "0"),
+                        ("StartPeriod", "0"),
+                        ("StartRange", "0"),
+                    ],
+                )
+            }
         }
     }
 
@@ -81,45 +512,288 @@ impl Endpoint {
             Endpoint::TeamsGeneralAdvanced => "fetched_TeamsGeneralAdvanced_data",
             Endpoint::OpponentShootingGeneral => "fetched_OpponentShootingGeneral_data",
             Endpoint::TeamsGeneralOpponent => "fetched_TeamsGeneralOpponent_data",
+            Endpoint::PlayerGameLogs => "fetched_PlayerGameLogs_data",
+            Endpoint::TeamGameLogs => "fetched_TeamGameLogs_data",
+            Endpoint::Lineups => "fetched_Lineups_data",
+            Endpoint::PlayerOnOff => "fetched_PlayerOnOff_data",
+            Endpoint::Standings => "fetched_Standings_data",
+            Endpoint::PlayerTracking(PtMeasureType::SpeedDistance) => {
+                "fetched_PlayerTrackingSpeedDistance_data"
+            }
+            Endpoint::PlayerTracking(PtMeasureType::Possessions) => {
+                "fetched_PlayerTrackingPossessions_data"
+            }
+            Endpoint::PlayerTracking(PtMeasureType::Drives) => "fetched_PlayerTrackingDrives_data",
+            Endpoint::PlayerTracking(PtMeasureType::CatchShoot) => {
+                "fetched_PlayerTrackingCatchShoot_data"
+            }
+            Endpoint::PlayerTracking(PtMeasureType::PullUpShot) => {
+                "fetched_PlayerTrackingPullUpShot_data"
+            }
+            Endpoint::PlayerTracking(PtMeasureType::Rebounding) => {
+                "fetched_PlayerTrackingRebounding_data"
+            }
+            Endpoint::PlayerTracking(PtMeasureType::Defense) => {
+                "fetched_PlayerTrackingDefense_data"
+            }
+            Endpoint::TeamTracking(PtMeasureType::SpeedDistance) => {
+                "fetched_TeamTrackingSpeedDistance_data"
+            }
+            Endpoint::TeamTracking(PtMeasureType::Possessions) => {
+                "fetched_TeamTrackingPossessions_data"
+            }
+            Endpoint::TeamTracking(PtMeasureType::Drives) => "fetched_TeamTrackingDrives_data",
+            Endpoint::TeamTracking(PtMeasureType::CatchShoot) => {
+                "fetched_TeamTrackingCatchShoot_data"
+            }
+            Endpoint::TeamTracking(PtMeasureType::PullUpShot) => {
+                "fetched_TeamTrackingPullUpShot_data"
+            }
+            Endpoint::TeamTracking(PtMeasureType::Rebounding) => {
+                "fetched_TeamTrackingRebounding_data"
+            }
+            Endpoint::TeamTracking(PtMeasureType::Defense) => "fetched_TeamTrackingDefense_data",
+            Endpoint::PlayerHustle => "fetched_PlayerHustle_data",
+            Endpoint::TeamHustle => "fetched_TeamHustle_data",
+            Endpoint::ShotChart => "fetched_ShotChart_data",
             Endpoint::InjuryReport => "fetched_InjuryReport_data",
             Endpoint::Bet365Odds => "fetched_Bet365Odds_data",
+            Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame) => {
+                "fetched_OddsMoneyLineFullGame_data"
+            }
+            Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstHalf) => {
+                "fetched_OddsMoneyLineFirstHalf_data"
+            }
+            Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstQuarter) => {
+                "fetched_OddsMoneyLineFirstQuarter_data"
+            }
+            Endpoint::Odds(OddsMarket::Spread, OddsScope::FullGame) => {
+                "fetched_OddsSpreadFullGame_data"
+            }
+            Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstHalf) => {
+                "fetched_OddsSpreadFirstHalf_data"
+            }
+            Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstQuarter) => {
+                "fetched_OddsSpreadFirstQuarter_data"
+            }
+            Endpoint::Odds(OddsMarket::Total, OddsScope::FullGame) => {
+                "fetched_OddsTotalFullGame_data"
+            }
+            Endpoint::Odds(OddsMarket::Total, OddsScope::FirstHalf) => {
+                "fetched_OddsTotalFirstHalf_data"
+            }
+            Endpoint::Odds(OddsMarket::Total, OddsScope::FirstQuarter) => {
+                "fetched_OddsTotalFirstQuarter_data"
+            }
+            Endpoint::OddsLandingPage => "fetched_OddsLandingPage_data",
+            Endpoint::BoxScoreTraditional => "fetched_BoxScoreTraditional_data",
+            Endpoint::BoxScoreAdvanced => "fetched_BoxScoreAdvanced_data",
+            Endpoint::BoxScoreFourFactors => "fetched_BoxScoreFourFactors_data",
+            Endpoint::BoxScoreUsage => "fetched_BoxScoreUsage_data",
+            Endpoint::PlayByPlay => "fetched_PlayByPlay_data",
         }
     }
-    pub fn prepared_data_file_path() -> PathBuf {
+
+    /// The measure type the endpoint asks for unless the query names one,
+    /// for the endpoints that take one. Their row types describe its columns.
+    pub fn default_measure_type(&self) -> Option<MeasureType> {
+        match self {
+            Endpoint::PlayerGeneralAverages
+            | Endpoint::TeamsGeneralOpponent
+            | Endpoint::PlayerOnOff => Some(MeasureType::Base),
+            Endpoint::TeamsGeneralAdvanced | Endpoint::Lineups => Some(MeasureType::Advanced),
+            _ => None,
+        }
+    }
+
+    /// Whether `query` asks for a measure type other than the default, so
+    /// the response has columns the endpoint's row type doesn't describe.
+    pub fn overrides_measure_type(&self, query: &StatsQuery) -> bool {
+        match (self.default_measure_type(), query.measure_type()) {
+            (Some(default), Some(asked)) => asked != default,
+            _ => false,
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Endpoint::SeasonSchedule => "json",
+            _ => "csv",
+        }
+    }
+
+    pub fn data_file_path() -> PathBuf {
         let parent = env::current_dir().unwrap();
         let cwd = parent.parent().unwrap();
-        let file_path = Path::new(&cwd).join("data").join("prepared_data");
+        // "../data"
+        Path::new(&cwd).join("data")
+    }
+
+    pub fn prepared_data_file_path() -> PathBuf {
         // "../data/prepared_data"
-        file_path
+        Self::data_file_path().join("prepared_data")
+    }
+
+    /// Partition for one season of historical data, e.g.
+    /// `../data/history/2023-24/regular_season`.
+    pub fn history_data_file_path(season: Season, season_type: SeasonType) -> PathBuf {
+        Self::data_file_path()
+            .join("history")
+            .join(season.to_string())
+            .join(season_type.slug())
+    }
+
+    /// Box scores for one season, e.g. `../data/box_scores/2024-25`. They
+    /// accumulate game by game rather than being refetched as a whole.
+    pub fn box_scores_file_path(season: Season) -> PathBuf {
+        Self::data_file_path()
+            .join("box_scores")
+            .join(season.to_string())
+    }
+
+    /// Play-by-play for one season, e.g. `../data/play_by_play/2024-25`,
+    /// filled in game by game like the box scores.
+    pub fn play_by_play_file_path(season: Season) -> PathBuf {
+        Self::data_file_path()
+            .join("play_by_play")
+            .join(season.to_string())
     }
 }
 
-pub async fn fetch_data<T>(url: &str, file_name: &str) -> Result<T, FetchError>
-where
-    T: DeserializeOwned,
-{
-    let client = Client::new();
-    let headers = build_headers();
+/// Where a fetch writes its output: the raw response body as text and the
+/// prepared CSV/JSON the wrangler reads.
+#[derive(Debug, Clone)]
+pub struct OutputDirs {
+    pub raw: PathBuf,
+    pub prepared: PathBuf,
+}
 
-    debug!("Attempting to fetch data from: {}", url);
+impl OutputDirs {
+    /// The `fetched_data_output_as_string` / `prepared_data` pair under `root`.
+    pub fn under(root: &Path) -> Self {
+        OutputDirs {
+            raw: root.join("fetched_data_output_as_string"),
+            prepared: root.join("prepared_data"),
+        }
+    }
 
-    let response = client.get(url).headers(headers).send().await?;
+    pub fn create(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.raw)?;
+        fs::create_dir_all(&self.prepared)
+    }
 
-    if !response.status().is_success() {
-        error!("Unexpected status code: {}", response.status());
-        return Err(FetchError::UnexpectedStatusCode(response.status()));
+    /// The run's `fetch_manifest.json`, beside `prepared_data`.
+    pub fn manifest(&self) -> PathBuf {
+        self.prepared.with_file_name(manifest::MANIFEST_FILE)
     }
 
-    let bytes = response.bytes().await?;
-    let data = decompress_or_convert(&bytes, file_name)?;
+    /// The raw response saved for `endpoint`. On/off splits are saved once
+    /// per team; the first team's that is there will do since they share a
+    /// shape.
+    pub fn raw_response(&self, endpoint: Endpoint) -> PathBuf {
+        match endpoint {
+            Endpoint::PlayerOnOff => TEAM_IDS
+                .map(|team_id| player_on_off_raw_path(self, team_id))
+                .find(|path| path.exists())
+                .unwrap_or_else(|| player_on_off_raw_path(self, *TEAM_IDS.start())),
+            _ => self.raw.join(endpoint.file_name()).with_extension("txt"),
+        }
+    }
+}
+
+impl Default for OutputDirs {
+    /// `../data/fetched_data_output_as_string` and `../data/prepared_data`.
+    fn default() -> Self {
+        OutputDirs::under(&Endpoint::data_file_path())
+    }
+}
+
+/// Everything a fetcher needs: what to ask for, where to write it, where
+/// the responses come from, what shape they are expected to have and which
+/// build the odds site is serving.
+#[derive(Debug, Clone)]
+pub struct FetchContext {
+    pub query: StatsQuery,
+    pub dirs: OutputDirs,
+    pub transport: Arc<dyn Transport>,
+    pub schemas: Arc<SchemaRegistry>,
+    pub odds_build: Arc<BuildIdCache>,
+    pub requests: Arc<RequestLog>,
+}
+
+impl FetchContext {
+    /// A context that fetches over HTTP.
+    pub fn new(query: StatsQuery, dirs: OutputDirs) -> Self {
+        FetchContext {
+            query,
+            dirs,
+            transport: http_transport(),
+            schemas: Arc::new(SchemaRegistry::default()),
+            odds_build: Arc::new(BuildIdCache::default()),
+            requests: Arc::new(RequestLog::default()),
+        }
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn with_schemas(mut self, schemas: Arc<SchemaRegistry>) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    pub fn with_odds_build(mut self, odds_build: Arc<BuildIdCache>) -> Self {
+        self.odds_build = odds_build;
+        self
+    }
+
+    /// The same context without keeping its requests for a manifest, so a
+    /// mode that fetches until stopped doesn't collect them forever.
+    pub fn without_request_log(mut self) -> Self {
+        self.requests = Arc::new(RequestLog::disabled());
+        self
+    }
+}
+
+pub async fn fetch_data<T>(
+    ctx: &FetchContext,
+    endpoint: Endpoint,
+    url: &str,
+    file_name: &str,
+) -> Result<T, FetchError>
+where
+    T: DeserializeOwned,
+{
+    debug!("Attempting to fetch data from: {}", url);
+
+    let response = ctx.transport.get(endpoint, url).await;
+    ctx.requests.record(
+        endpoint,
+        FetchedRequest {
+            url: url.to_string(),
+            status: match &response {
+                Ok(response) => response.status.map(|s| s.as_u16()),
+                Err(e) => e.status().map(|s| s.as_u16()),
+            },
+            raw_file: response.is_ok().then(|| format!("{}.txt", file_name)),
+        },
+    );
+    let response = response.inspect_err(|e| {
+        error!("Failed to fetch {}: {}", url, e);
+    })?;
+    let data = decompress_or_convert(&response.body, file_name, &ctx.dirs.raw)?;
+    let value: Value = serde_json::from_str(&data)?;
+    ctx.schemas.check(endpoint, &value);
 
     info!("Successfully fetched and parsed data from: {}", url);
-    Ok(serde_json::from_str(&data)?)
+    Ok(serde_json::from_value(value)?)
 }
 
-fn write_to_file(path: &str, filename: &str, content: &str) -> io::Result<()> {
+fn write_to_file(path: &Path, filename: &str, content: &str) -> io::Result<()> {
     fs::create_dir_all(path)?; // Create the directory if it doesn't exist
-    let mut file_path = Path::new(path).join(filename);
+    let mut file_path = path.join(filename);
     file_path.set_extension("txt");
     let mut file = File::create(file_path)?;
     file.write_all(content.as_bytes())?;
@@ -156,39 +830,257 @@ fn build_headers() -> HeaderMap {
     headers
 }
 
-fn decompress_or_convert(bytes: &[u8], file_name: &str) -> Result<String, FetchError> {
+fn decompress_or_convert(
+    bytes: &[u8],
+    file_name: &str,
+    raw_dir: &Path,
+) -> Result<String, FetchError> {
     let mut decoder = GzDecoder::new(bytes);
     let mut decompressed_data = String::new();
 
     match decoder.read_to_string(&mut decompressed_data) {
         Ok(_) => {
-            write_to_file(
-                "../data/fetched_data_output_as_string",
-                file_name,
-                &decompressed_data,
-            )?;
+            write_to_file(raw_dir, file_name, &decompressed_data)?;
             Ok(decompressed_data)
         }
         Err(_) => {
             // If decompression fails, try converting bytes directly to a UTF-8 string
             let converted_data = String::from_utf8(bytes.to_vec()).map_err(FetchError::Utf8)?;
-            write_to_file(
-                "../data/fetched_data_output_as_string",
-                file_name,
-                &converted_data,
-            )?;
+            write_to_file(raw_dir, file_name, &converted_data)?;
             Ok(converted_data)
         }
     }
 }
 
-pub async fn gather_and_prepare_fetched_data() {
-    fetch_teams_general_opponent().await.unwrap();
-    fetch_teams_general_advanced().await.unwrap();
-    fetch_season_schedule().await.unwrap();
-    fetch_player_index().await.unwrap();
-    fetch_player_general_averages().await.unwrap();
-    fetch_opponent_shooting_general().await.unwrap();
-    fetch_injury_report().await.unwrap();
-    fetch_bet_ml_odds().await.unwrap();
+/// Everything a regular run refreshes. bet365's moneylines are written from
+/// the full-game moneyline page, so [`Endpoint::Bet365Odds`] isn't fetched
+/// on its own.
+pub const DAILY_ENDPOINTS: [Endpoint; 38] = [
+    Endpoint::TeamsGeneralOpponent,
+    Endpoint::TeamsGeneralAdvanced,
+    Endpoint::SeasonSchedule,
+    Endpoint::PlayerIndex,
+    Endpoint::PlayerGeneralAverages,
+    Endpoint::OpponentShootingGeneral,
+    Endpoint::PlayerGameLogs,
+    Endpoint::TeamGameLogs,
+    Endpoint::Lineups,
+    Endpoint::PlayerOnOff,
+    Endpoint::Standings,
+    Endpoint::PlayerTracking(PtMeasureType::SpeedDistance),
+    Endpoint::PlayerTracking(PtMeasureType::Possessions),
+    Endpoint::PlayerTracking(PtMeasureType::Drives),
+    Endpoint::PlayerTracking(PtMeasureType::CatchShoot),
+    Endpoint::PlayerTracking(PtMeasureType::PullUpShot),
+    Endpoint::PlayerTracking(PtMeasureType::Rebounding),
+    Endpoint::PlayerTracking(PtMeasureType::Defense),
+    Endpoint::TeamTracking(PtMeasureType::SpeedDistance),
+    Endpoint::TeamTracking(PtMeasureType::Possessions),
+    Endpoint::TeamTracking(PtMeasureType::Drives),
+    Endpoint::TeamTracking(PtMeasureType::CatchShoot),
+    Endpoint::TeamTracking(PtMeasureType::PullUpShot),
+    Endpoint::TeamTracking(PtMeasureType::Rebounding),
+    Endpoint::TeamTracking(PtMeasureType::Defense),
+    Endpoint::PlayerHustle,
+    Endpoint::TeamHustle,
+    Endpoint::InjuryReport,
+    Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame),
+    Endpoint::Odds(OddsMarket::Spread, OddsScope::FullGame),
+    Endpoint::Odds(OddsMarket::Total, OddsScope::FullGame),
+    Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstHalf),
+    Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstHalf),
+    Endpoint::Odds(OddsMarket::Total, OddsScope::FirstHalf),
+    Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstQuarter),
+    Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstQuarter),
+    Endpoint::Odds(OddsMarket::Total, OddsScope::FirstQuarter),
+    Endpoint::TodaysScoreboard,
+];
+
+/// Endpoints a regular run only refreshes when asked to, on top of
+/// [`DAILY_ENDPOINTS`]: the league-wide shot chart is every shot of the
+/// season so far.
+pub const OPT_IN_ENDPOINTS: [Endpoint; 1] = [Endpoint::ShotChart];
+
+/// Runs the fetcher for `endpoint`.
+pub async fn fetch_endpoint(endpoint: Endpoint, ctx: &FetchContext) -> FetchOutcome {
+    let result = match endpoint {
+        Endpoint::SeasonSchedule => fetch_season_schedule(ctx).await,
+        Endpoint::PlayerIndex => fetch_player_index(ctx).await,
+        Endpoint::PlayerGeneralAverages => fetch_player_general_averages(ctx).await,
+        Endpoint::TeamsGeneralAdvanced => fetch_teams_general_advanced(ctx).await,
+        Endpoint::OpponentShootingGeneral => fetch_opponent_shooting_general(ctx).await,
+        Endpoint::TeamsGeneralOpponent => fetch_teams_general_opponent(ctx).await,
+        Endpoint::PlayerGameLogs => fetch_player_game_logs(ctx).await,
+        Endpoint::TeamGameLogs => fetch_team_game_logs(ctx).await,
+        Endpoint::Lineups => fetch_lineups(ctx).await,
+        Endpoint::PlayerOnOff => fetch_player_on_off(ctx).await,
+        Endpoint::Standings => fetch_standings(ctx).await,
+        Endpoint::PlayerTracking(_) | Endpoint::TeamTracking(_) => {
+            fetch_tracking(endpoint, ctx).await
+        }
+        Endpoint::PlayerHustle | Endpoint::TeamHustle => fetch_hustle(endpoint, ctx).await,
+        Endpoint::ShotChart => fetch_shot_chart(ctx).await,
+        Endpoint::InjuryReport => fetch_injury_report(ctx).await,
+        Endpoint::Bet365Odds => fetch_bet_ml_odds(ctx).await,
+        Endpoint::Odds(..) => fetch_market_odds(endpoint, ctx).await,
+        Endpoint::OddsLandingPage => fetch_odds_build_id(ctx).await,
+        Endpoint::TodaysScoreboard => fetch_todays_scoreboard(ctx).await,
+        Endpoint::BoxScoreTraditional
+        | Endpoint::BoxScoreAdvanced
+        | Endpoint::BoxScoreFourFactors
+        | Endpoint::BoxScoreUsage => fetch_box_score(endpoint, ctx).await,
+        Endpoint::PlayByPlay => fetch_play_by_play(ctx).await,
+    };
+    match result {
+        Ok(()) => FetchOutcome::Success,
+        Err(e) => FetchOutcome::Failed(e),
+    }
+}
+
+/// Fetches `endpoints`, usually [`DAILY_ENDPOINTS`], into a new
+/// point-in-time snapshot, then promotes that snapshot to the `latest` data
+/// the wrangler reads.
+///
+/// Endpoints are fetched `concurrency` at a time; one failing doesn't stop
+/// the rest, and what succeeded is still promoted. Only errors creating or
+/// promoting the snapshot itself are returned as `Err`.
+pub async fn gather_and_prepare_fetched_data(
+    query: &StatsQuery,
+    endpoints: &[Endpoint],
+    transport: Arc<dyn Transport>,
+    concurrency: usize,
+) -> Result<FetchReport, FetchError> {
+    gather_into(
+        &Endpoint::data_file_path(),
+        query,
+        endpoints,
+        transport,
+        concurrency,
+    )
+    .await
+}
+
+/// [`gather_and_prepare_fetched_data`] against an arbitrary data directory,
+/// laid out like `../data`.
+pub async fn gather_into(
+    data_dir: &Path,
+    query: &StatsQuery,
+    endpoints: &[Endpoint],
+    transport: Arc<dyn Transport>,
+    concurrency: usize,
+) -> Result<FetchReport, FetchError> {
+    let snapshot = Snapshot::begin_in(&data_dir.join("snapshots"), Utc::now())?;
+    let ctx = FetchContext::new(query.clone(), snapshot.dirs().clone()).with_transport(transport);
+    let report = fetch_concurrently(endpoints, &ctx, concurrency).await;
+    FetchManifest::build(&report, &ctx)?.write(&ctx.dirs.manifest())?;
+    snapshot.promote_to_latest(&OutputDirs::under(data_dir))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock_server::{MockResponse, MockServer};
+    use transport::FixtureTransport;
+
+    #[tokio::test]
+    async fn pipeline_runs_offline_from_seed_data() {
+        let data_dir = env::temp_dir().join("nba_offline_pipeline");
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let report = gather_into(
+            &data_dir,
+            &StatsQuery::default(),
+            &DAILY_ENDPOINTS,
+            Arc::new(FixtureTransport::seed_data()),
+            2,
+        )
+        .await
+        .unwrap();
+
+        assert!(report.is_success(), "{}", report);
+        assert!(report.drifted().next().is_none(), "{}", report);
+        let latest = OutputDirs::under(&data_dir);
+        for endpoint in DAILY_ENDPOINTS {
+            let mut prepared = latest.prepared.join(endpoint.file_name());
+            prepared.set_extension(endpoint.file_extension());
+            assert!(prepared.exists(), "{:?} was not prepared", endpoint);
+        }
+        assert!(latest.prepared.join("fetched_Bet365Odds_data.csv").exists());
+        let manifest = manifest::FetchManifest::read(&latest.manifest()).unwrap();
+        assert_eq!(manifest.endpoints.len(), DAILY_ENDPOINTS.len());
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fetchers_write_prepared_data_from_http() {
+        let server = MockServer::start().await;
+        let seed = Endpoint::data_file_path().join("seed_data");
+        let serve = |endpoint: Endpoint| {
+            MockResponse::file(&seed.join(transport::fixture_file_name(endpoint)))
+        };
+        server
+            .route(
+                "MeasureType=Advanced",
+                vec![serve(Endpoint::TeamsGeneralAdvanced).gzipped()],
+            )
+            .route(
+                "/stats/leaguedashteamstats",
+                vec![serve(Endpoint::TeamsGeneralOpponent)],
+            )
+            .route(
+                "/stats/playerindex",
+                vec![
+                    MockResponse::status(503),
+                    serve(Endpoint::PlayerIndex).gzipped(),
+                ],
+            )
+            .route(
+                "/stats/leaguedashplayerstats",
+                vec![serve(Endpoint::PlayerGeneralAverages).truncated()],
+            );
+
+        let dir = env::temp_dir().join("nba_mock_server_fetch");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport());
+
+        let endpoints = [
+            Endpoint::TeamsGeneralAdvanced,
+            Endpoint::TeamsGeneralOpponent,
+            Endpoint::PlayerIndex,
+            Endpoint::PlayerGeneralAverages,
+            Endpoint::InjuryReport,
+        ];
+        let report = fetch_concurrently(&endpoints, &ctx, 2).await;
+
+        for endpoint in &endpoints[..3] {
+            let prepared = dirs
+                .prepared
+                .join(endpoint.file_name())
+                .with_extension(endpoint.file_extension());
+            let csv = fs::read_to_string(&prepared).unwrap();
+            assert!(csv.lines().count() > 1, "{:?} wrote no rows", endpoint);
+            assert!(dirs
+                .raw
+                .join(format!("{}.txt", endpoint.file_name()))
+                .exists());
+        }
+        assert_eq!(server.hits("/stats/playerindex"), 2);
+
+        let failed: Vec<_> = report.failures().map(|r| r.endpoint).collect();
+        assert_eq!(
+            failed,
+            vec![Endpoint::PlayerGeneralAverages, Endpoint::InjuryReport]
+        );
+        assert!(matches!(
+            report.endpoints[4].outcome,
+            FetchOutcome::Failed(FetchError::PermanentFailure(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
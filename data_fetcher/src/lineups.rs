@@ -0,0 +1,339 @@
+use crate::{
+    error::FetchError,
+    fetch_data,
+    pipeline::{join_failure, DEFAULT_CONCURRENCY},
+    query::TEAM_IDS,
+    result_sets::{
+        result_set_row, write_result_set, write_rows_to_csv, write_vector_to_csv,
+        ResultSetsFetchedResponse,
+    },
+    Endpoint, FetchContext, OutputDirs,
+};
+use serde_json::Value;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing::error;
+
+result_set_row! {
+    /// One five-man lineup from `leaguedashlineups` with
+    /// `MeasureType=Advanced`. `GROUP_ID` is the player ids joined with
+    /// `-`, e.g. `-1630595-1631105-...-`.
+    pub struct LineupRow {
+        "GROUP_SET" => group_set: String,
+        "GROUP_ID" => group_id: String,
+        "GROUP_NAME" => group_name: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "GP" => gp: i64,
+        "W" => w: i64,
+        "L" => l: i64,
+        "W_PCT" => w_pct: f64,
+        "MIN" => min: f64,
+        "E_OFF_RATING" => e_off_rating: f64,
+        "OFF_RATING" => off_rating: f64,
+        "E_DEF_RATING" => e_def_rating: f64,
+        "DEF_RATING" => def_rating: f64,
+        "E_NET_RATING" => e_net_rating: f64,
+        "NET_RATING" => net_rating: f64,
+        "AST_PCT" => ast_pct: f64,
+        "AST_TO" => ast_to: f64,
+        "AST_RATIO" => ast_ratio: f64,
+        "OREB_PCT" => oreb_pct: f64,
+        "DREB_PCT" => dreb_pct: f64,
+        "REB_PCT" => reb_pct: f64,
+        "TM_TOV_PCT" => tm_tov_pct: f64,
+        "EFG_PCT" => efg_pct: f64,
+        "TS_PCT" => ts_pct: f64,
+        "E_PACE" => e_pace: f64,
+        "PACE" => pace: f64,
+        "PACE_PER40" => pace_per40: f64,
+        "POSS" => poss: i64,
+        "PIE" => pie: f64,
+        "GP_RANK" => gp_rank: i64,
+        "W_RANK" => w_rank: i64,
+        "L_RANK" => l_rank: i64,
+        "W_PCT_RANK" => w_pct_rank: i64,
+        "MIN_RANK" => min_rank: i64,
+        "OFF_RATING_RANK" => off_rating_rank: i64,
+        "DEF_RATING_RANK" => def_rating_rank: i64,
+        "NET_RATING_RANK" => net_rating_rank: i64,
+        "AST_PCT_RANK" => ast_pct_rank: i64,
+        "AST_TO_RANK" => ast_to_rank: i64,
+        "AST_RATIO_RANK" => ast_ratio_rank: i64,
+        "OREB_PCT_RANK" => oreb_pct_rank: i64,
+        "DREB_PCT_RANK" => dreb_pct_rank: i64,
+        "REB_PCT_RANK" => reb_pct_rank: i64,
+        "TM_TOV_PCT_RANK" => tm_tov_pct_rank: i64,
+        "EFG_PCT_RANK" => efg_pct_rank: i64,
+        "TS_PCT_RANK" => ts_pct_rank: i64,
+        "PACE_RANK" => pace_rank: i64,
+        "PIE_RANK" => pie_rank: i64,
+    }
+}
+
+result_set_row! {
+    /// A team's results with one player on (`COURT_STATUS` is `On`) or off
+    /// the court, from `teamplayeronoffsummary`.
+    pub struct PlayerOnOffRow {
+        "GROUP_SET" => group_set: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "TEAM_NAME" => team_name: String,
+        "VS_PLAYER_ID" => vs_player_id: i64,
+        "VS_PLAYER_NAME" => vs_player_name: String,
+        "COURT_STATUS" => court_status: String,
+        "GP" => gp: i64,
+        "MIN" => min: f64,
+        "PLUS_MINUS" => plus_minus: f64,
+        "OFF_RATING" => off_rating: f64,
+        "DEF_RATING" => def_rating: f64,
+        "NET_RATING" => net_rating: f64,
+    }
+}
+
+const ON_COURT: &str = "PlayersOnCourtTeamPlayerOnOffSummary";
+const OFF_COURT: &str = "PlayersOffCourtTeamPlayerOnOffSummary";
+
+pub async fn fetch_lineups(ctx: &FetchContext) -> Result<(), FetchError> {
+    // API Endpoint
+    let endpoint = Endpoint::Lineups.url(&ctx.query);
+    // File Name for future reference
+    let file_name = Endpoint::Lineups.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+    // Fetch Data
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::Lineups, &endpoint, file_name).await?;
+    write_result_set::<LineupRow>(
+        data.first_result_set()?,
+        Endpoint::Lineups,
+        &ctx.query,
+        save_file_path,
+    )?;
+    Ok(())
+}
+
+/// Where the raw on/off response for `team_id` is kept: one file per team
+/// in [`OutputDirs::raw`], e.g. `fetched_PlayerOnOff_data_1610612737.txt`.
+pub fn player_on_off_raw_path(dirs: &OutputDirs, team_id: u32) -> PathBuf {
+    dirs.raw
+        .join(player_on_off_raw_name(team_id))
+        .with_extension("txt")
+}
+
+fn player_on_off_raw_name(team_id: u32) -> String {
+    format!("{}_{}", Endpoint::PlayerOnOff.file_name(), team_id)
+}
+
+async fn fetch_team_on_off(
+    ctx: &FetchContext,
+    team_id: u32,
+) -> Result<ResultSetsFetchedResponse, FetchError> {
+    let query = ctx.query.clone().with_team_id(team_id);
+    let url = Endpoint::PlayerOnOff.url(&query);
+    fetch_data(
+        ctx,
+        Endpoint::PlayerOnOff,
+        &url,
+        &player_on_off_raw_name(team_id),
+    )
+    .await
+}
+
+/// On/off splits for the team in `ctx.query`, or for every team if it names
+/// none, written as one table with both `On` and `Off` rows per player.
+///
+/// Teams are fetched a few at a time. One failing doesn't stop the others:
+/// the teams that came back are written and the ones that didn't are named
+/// in the error.
+pub async fn fetch_player_on_off(ctx: &FetchContext) -> Result<(), FetchError> {
+    let file_name = Endpoint::PlayerOnOff.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+
+    let team_ids: Vec<u32> = match ctx.query.team_id() {
+        Some(team_id) => vec![team_id],
+        None => TEAM_IDS.collect(),
+    };
+    let permits = Arc::new(Semaphore::new(DEFAULT_CONCURRENCY));
+    let mut tasks = JoinSet::new();
+    let mut spawned = HashMap::new();
+    for team_id in team_ids {
+        let permits = Arc::clone(&permits);
+        let ctx = ctx.clone();
+        let task = tasks.spawn(async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            fetch_team_on_off(&ctx, team_id).await
+        });
+        spawned.insert(task.id(), team_id);
+    }
+    let mut responses = Vec::new();
+    let mut failures = Vec::new();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, result) = match joined {
+            Ok((id, result)) => (id, result),
+            Err(e) => (e.id(), Err(join_failure(e))),
+        };
+        match result {
+            Ok(data) => responses.push((spawned[&id], data)),
+            Err(e) => failures.push((spawned[&id], e)),
+        }
+    }
+    // Rows in team order regardless of completion order.
+    responses.sort_by_key(|(team_id, _)| *team_id);
+
+    // Another measure type sends other columns, which are kept as sent.
+    let as_sent = Endpoint::PlayerOnOff.overrides_measure_type(&ctx.query);
+    let mut rows: Vec<PlayerOnOffRow> = Vec::new();
+    let mut sent_headers: Option<Vec<String>> = None;
+    let mut sent_rows: Vec<Vec<Value>> = Vec::new();
+    for (team_id, data) in responses {
+        let team = i64::from(team_id);
+        let result = [ON_COURT, OFF_COURT].into_iter().try_for_each(|set| {
+            let set = data.result_set(set)?;
+            if as_sent {
+                let team_column = set.headers.iter().position(|h| h == "TEAM_ID");
+                sent_rows.extend(
+                    set.rowSet
+                        .iter()
+                        .filter(|row| {
+                            team_column
+                                .is_none_or(|i| row.get(i).and_then(Value::as_i64) == Some(team))
+                        })
+                        .cloned(),
+                );
+                sent_headers.get_or_insert_with(|| set.headers.clone());
+                return Ok(());
+            }
+            // A response for some other team would otherwise be counted
+            // once for every team asked about.
+            let team_rows: Vec<PlayerOnOffRow> = set.rows()?;
+            rows.extend(team_rows.into_iter().filter(|row| row.team_id == team));
+            Ok(())
+        });
+        if let Err(e) = result {
+            failures.push((team_id, e));
+        }
+    }
+
+    if as_sent {
+        write_vector_to_csv(sent_headers, sent_rows, save_file_path)?;
+    } else {
+        write_rows_to_csv(&rows, save_file_path)?;
+    }
+    if failures.is_empty() {
+        return Ok(());
+    }
+    failures.sort_by_key(|(team_id, _)| *team_id);
+    for (team_id, e) in &failures {
+        error!("PlayerOnOff for team {} failed: {}", team_id, e);
+    }
+    Err(FetchError::TeamsFailed(
+        failures
+            .iter()
+            .map(|(team_id, e)| format!("{}: {}", team_id, e))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_server::{MockResponse, MockServer},
+        query::{MeasureType, StatsQuery},
+        transport::FixtureTransport,
+        OutputDirs,
+    };
+    use std::{env, fs, sync::Arc};
+
+    #[tokio::test]
+    async fn on_off_rows_pair_up_per_player() {
+        let dir = env::temp_dir().join("nba_player_on_off");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_player_on_off(&ctx).await.unwrap();
+
+        let mut reader =
+            csv::Reader::from_path(dirs.prepared.join("fetched_PlayerOnOff_data.csv")).unwrap();
+        let rows: Vec<PlayerOnOffRow> = reader.deserialize().map(Result::unwrap).collect();
+        // The fixture is Detroit's, so only that team's request keeps rows.
+        assert_eq!(rows.len(), 4);
+        let cade: Vec<_> = rows.iter().filter(|r| r.vs_player_id == 1630595).collect();
+        assert_eq!(cade[0].court_status, "On");
+        assert_eq!(cade[1].court_status, "Off");
+        assert!((cade[0].net_rating - cade[1].net_rating - 15.4).abs() < 1e-9);
+        assert!(player_on_off_raw_path(&dirs, 1610612765).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn other_measure_types_are_written_as_sent() {
+        let dir = env::temp_dir().join("nba_lineups_measure_type");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        // The fixture stands in for a Usage response: its columns are
+        // written whatever they are instead of failing against LineupRow.
+        let query = StatsQuery::default().with_measure_type(MeasureType::Usage);
+        let ctx = FetchContext::new(query, dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_lineups(&ctx).await.unwrap();
+
+        let raw: ResultSetsFetchedResponse =
+            serde_json::from_slice(&fs::read(dirs.raw.join("fetched_Lineups_data.txt")).unwrap())
+                .unwrap();
+        let sent = raw.first_result_set().unwrap();
+        let mut reader =
+            csv::Reader::from_path(dirs.prepared.join("fetched_Lineups_data.csv")).unwrap();
+        assert_eq!(reader.headers().unwrap(), sent.headers);
+        assert_eq!(reader.records().count(), sent.rowSet.len());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_failing_team_doesnt_stop_the_others() {
+        let dir = env::temp_dir().join("nba_player_on_off_failure");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let seed = Endpoint::data_file_path().join("seed_data");
+        let server = MockServer::start().await;
+        server
+            .route("TeamID=1610612737", vec![MockResponse::status(404)])
+            .route(
+                "/stats/teamplayeronoffsummary",
+                vec![MockResponse::file(
+                    &seed.join("teamplayeronoffsummary.json"),
+                )],
+            );
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport());
+
+        let error = fetch_player_on_off(&ctx).await.unwrap_err();
+        match &error {
+            FetchError::TeamsFailed(failed) => {
+                assert_eq!(failed.len(), 1);
+                assert!(failed[0].starts_with("1610612737: "));
+            }
+            other => panic!("expected failed teams, got {:?}", other),
+        }
+        assert_eq!(server.hits("/stats/teamplayeronoffsummary"), 29);
+        // Detroit's rows are still written.
+        let mut reader =
+            csv::Reader::from_path(dirs.prepared.join("fetched_PlayerOnOff_data.csv")).unwrap();
+        assert_eq!(reader.records().count(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    error::FetchError,
+    todays_scoreboard::{fetch_scoreboard, GameStatus, ScoreboardGame},
+    FetchContext,
+};
+use serde::Serialize;
+use std::{collections::HashMap, time::Duration};
+use tokio::{sync::broadcast, time::sleep};
+use tracing::{info, warn};
+
+/// Something that happened in a game between two scoreboard polls.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event")]
+pub enum GameEvent {
+    GameStarted {
+        game_id: String,
+    },
+    ScoreChanged {
+        game_id: String,
+        period: u32,
+        game_clock: String,
+        home_score: u32,
+        away_score: u32,
+    },
+    PeriodEnded {
+        game_id: String,
+        period: u32,
+        home_score: u32,
+        away_score: u32,
+    },
+    /// The result is official and bets on the game can be graded.
+    GameFinal {
+        game_id: String,
+        home_score: u32,
+        away_score: u32,
+    },
+}
+
+/// Events that turn `previous` into `current`, in the order they happened
+/// within each game. A game missing from `previous` is compared against a
+/// scheduled, scoreless one, so the first poll reports games already under
+/// way or finished.
+pub fn diff_games(previous: &[ScoreboardGame], current: &[ScoreboardGame]) -> Vec<GameEvent> {
+    let previous: HashMap<&str, &ScoreboardGame> =
+        previous.iter().map(|g| (g.game_id.as_str(), g)).collect();
+    let mut events = Vec::new();
+
+    for game in current {
+        let game_id = game.game_id.clone();
+        let home_score = game.home_team.score;
+        let away_score = game.away_team.score;
+        let (was_status, was_period, was_scores) = match previous.get(game.game_id.as_str()) {
+            Some(before) => (
+                before.game_status,
+                before.period,
+                (before.home_team.score, before.away_team.score),
+            ),
+            None => (GameStatus::Scheduled, 0, (0, 0)),
+        };
+
+        if was_status == GameStatus::Scheduled && game.game_status != GameStatus::Scheduled {
+            events.push(GameEvent::GameStarted {
+                game_id: game_id.clone(),
+            });
+        }
+        if was_scores != (home_score, away_score) {
+            events.push(GameEvent::ScoreChanged {
+                game_id: game_id.clone(),
+                period: game.period,
+                game_clock: game.game_clock.clone(),
+                home_score,
+                away_score,
+            });
+        }
+        if was_period > 0 && game.period > was_period {
+            events.push(GameEvent::PeriodEnded {
+                game_id: game_id.clone(),
+                period: was_period,
+                home_score: period_total(game, was_period, true),
+                away_score: period_total(game, was_period, false),
+            });
+        }
+        if was_status != GameStatus::Final && game.game_status == GameStatus::Final {
+            events.push(GameEvent::GameFinal {
+                game_id,
+                home_score,
+                away_score,
+            });
+        }
+    }
+    events
+}
+
+/// A team's score at the end of `period`.
+fn period_total(game: &ScoreboardGame, period: u32, home: bool) -> u32 {
+    let team = if home {
+        &game.home_team
+    } else {
+        &game.away_team
+    };
+    team.periods
+        .iter()
+        .filter(|p| p.period <= period)
+        .map(|p| p.score)
+        .sum()
+}
+
+#[derive(Debug, Clone)]
+pub struct PollConfig {
+    /// Between polls while any game is in progress.
+    pub live_interval: Duration,
+    /// Between polls while today's games have yet to start.
+    pub idle_interval: Duration,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        PollConfig {
+            live_interval: Duration::from_secs(20),
+            idle_interval: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// Polls today's scoreboard and broadcasts a [`GameEvent`] for every change.
+///
+/// Subscribe before calling [`run`](Self::run) so no events are missed.
+#[derive(Debug)]
+pub struct LivePoller {
+    ctx: FetchContext,
+    config: PollConfig,
+    events: broadcast::Sender<GameEvent>,
+}
+
+impl LivePoller {
+    /// A poller fetching through `ctx`. It polls until the games end and
+    /// writes no manifest, so its requests aren't kept.
+    pub fn new(ctx: FetchContext, config: PollConfig) -> Self {
+        let (events, _) = broadcast::channel(256);
+        LivePoller {
+            ctx: ctx.without_request_log(),
+            config,
+            events,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<GameEvent> {
+        self.events.subscribe()
+    }
+
+    /// Polls until every game on the scoreboard is final, or straight away
+    /// if there are no games today. A failed poll is logged and retried at
+    /// the next interval.
+    pub async fn run(self) -> Result<(), FetchError> {
+        let mut previous: Vec<ScoreboardGame> = Vec::new();
+        loop {
+            let games = match fetch_scoreboard(&self.ctx).await {
+                Ok(scoreboard) => scoreboard.games,
+                Err(e) => {
+                    warn!("Scoreboard poll failed: {}", e);
+                    sleep(self.config.live_interval).await;
+                    continue;
+                }
+            };
+
+            for event in diff_games(&previous, &games) {
+                info!("{:?}", event);
+                // No subscribers is fine; the events are also logged.
+                let _ = self.events.send(event);
+            }
+
+            if games.iter().all(|g| g.game_status == GameStatus::Final) {
+                info!("No games left to follow today");
+                return Ok(());
+            }
+            let live = games
+                .iter()
+                .any(|g| g.game_status == GameStatus::InProgress);
+            previous = games;
+            sleep(if live {
+                self.config.live_interval
+            } else {
+                self.config.idle_interval
+            })
+            .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_server::{MockResponse, MockServer},
+        query::StatsQuery,
+        OutputDirs,
+    };
+    use serde_json::Value;
+    use std::{env, fs};
+
+    fn seed_scoreboard() -> Value {
+        let seed = fs::read("../data/seed_data/todaysScoreboard_00.json").unwrap();
+        serde_json::from_slice(&seed).unwrap()
+    }
+
+    /// The seed scoreboard with its first game set to `status` in `period`,
+    /// with `scores` for each period so far as (home, away), and every other
+    /// game final.
+    fn scoreboard(status: u8, period: u32, scores: &[(u32, u32)]) -> Value {
+        let mut json = seed_scoreboard();
+        let games = json["scoreboard"]["games"].as_array_mut().unwrap();
+        for (i, game) in games.iter_mut().enumerate() {
+            if i > 0 {
+                game["gameStatus"] = 3.into();
+                continue;
+            }
+            game["gameStatus"] = status.into();
+            game["period"] = period.into();
+            for (side, pick) in [("homeTeam", 0), ("awayTeam", 1)] {
+                let mut total = 0;
+                for (p, score) in scores.iter().enumerate() {
+                    let score = if pick == 0 { score.0 } else { score.1 };
+                    game[side]["periods"][p]["score"] = score.into();
+                    total += score;
+                }
+                game[side]["score"] = total.into();
+            }
+        }
+        json
+    }
+
+    fn games(json: &Value) -> Vec<ScoreboardGame> {
+        serde_json::from_value(json["scoreboard"]["games"].clone()).unwrap()
+    }
+
+    #[test]
+    fn diff_reports_each_kind_of_change() {
+        let scheduled = games(&scoreboard(1, 0, &[]));
+        let tipped_off = games(&scoreboard(2, 1, &[(2, 0)]));
+        let second_quarter = games(&scoreboard(2, 2, &[(28, 25), (3, 0)]));
+        let finished = games(&scoreboard(3, 4, &[(28, 25), (30, 20), (25, 30), (20, 22)]));
+
+        let id = "0022400181".to_string();
+        assert_eq!(
+            diff_games(&scheduled, &tipped_off),
+            vec![
+                GameEvent::GameStarted {
+                    game_id: id.clone()
+                },
+                GameEvent::ScoreChanged {
+                    game_id: id.clone(),
+                    period: 1,
+                    game_clock: String::new(),
+                    home_score: 2,
+                    away_score: 0,
+                },
+            ]
+        );
+        assert!(
+            diff_games(&second_quarter, &finished).contains(&GameEvent::PeriodEnded {
+                game_id: id.clone(),
+                period: 2,
+                home_score: 58,
+                away_score: 45,
+            })
+        );
+        assert_eq!(
+            diff_games(&tipped_off, &second_quarter)[1],
+            GameEvent::PeriodEnded {
+                game_id: id.clone(),
+                period: 1,
+                home_score: 28,
+                away_score: 25,
+            }
+        );
+        assert_eq!(
+            diff_games(&second_quarter, &finished).last(),
+            Some(&GameEvent::GameFinal {
+                game_id: id,
+                home_score: 103,
+                away_score: 97,
+            })
+        );
+        assert!(diff_games(&finished, &finished).is_empty());
+    }
+
+    #[tokio::test]
+    async fn poller_broadcasts_until_games_are_final() {
+        let server = MockServer::start().await;
+        let polls = [
+            scoreboard(1, 0, &[]),
+            scoreboard(2, 1, &[(5, 3)]),
+            scoreboard(3, 4, &[(28, 25), (30, 20), (25, 30), (20, 22)]),
+        ];
+        server.route(
+            "todaysScoreboard_00.json",
+            polls
+                .iter()
+                .map(|json| MockResponse::ok(json.to_string()))
+                .collect(),
+        );
+
+        let dir = env::temp_dir().join("nba_live_poller");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport());
+        let poller = LivePoller::new(
+            ctx,
+            PollConfig {
+                live_interval: Duration::from_millis(5),
+                idle_interval: Duration::from_millis(5),
+            },
+        );
+        let mut events = poller.subscribe();
+        poller.run().await.unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(server.hits("todaysScoreboard_00.json"), 3);
+        assert!(received.contains(&GameEvent::GameStarted {
+            game_id: "0022400181".to_string()
+        }));
+        assert!(matches!(
+            received.last(),
+            Some(GameEvent::GameFinal {
+                home_score: 103,
+                ..
+            })
+        ));
+        assert!(dirs
+            .prepared
+            .join("fetched_TodaysScoreboard_data.csv")
+            .exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
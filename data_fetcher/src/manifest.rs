@@ -0,0 +1,335 @@
+use crate::{
+    error::FetchError,
+    pipeline::{FetchOutcome, FetchReport},
+    Endpoint, FetchContext,
+};
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+    sync::Mutex,
+};
+
+/// Name of the manifest written next to `prepared_data`.
+pub const MANIFEST_FILE: &str = "fetch_manifest.json";
+
+/// Where every file of one run came from, so downstream steps can cite
+/// exactly which data they used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FetchManifest {
+    pub generated_at: DateTime<Utc>,
+    pub endpoints: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub endpoint: Endpoint,
+    /// Every request the fetch made, in order, e.g. one per team for
+    /// `PlayerOnOff`.
+    pub requests: Vec<ManifestRequest>,
+    pub fetched_at: DateTime<Utc>,
+    pub elapsed_ms: u128,
+    /// `ok`, `skipped` or `failed`.
+    pub outcome: String,
+    pub error: Option<String>,
+    pub prepared: Option<FileDigest>,
+    /// Records in the prepared file, not counting a header row.
+    pub rows: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestRequest {
+    pub url: String,
+    pub params: BTreeMap<String, String>,
+    /// The HTTP status answered, if a request went out; a fixture or a
+    /// fresh cache entry has none.
+    pub status: Option<u16>,
+    pub raw: Option<FileDigest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDigest {
+    pub file: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+impl FileDigest {
+    /// Digest of the file at `path`, or `None` if there isn't one.
+    pub fn of(path: &Path) -> io::Result<Option<Self>> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(Some(FileDigest {
+            file: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            bytes: contents.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&contents)),
+        }))
+    }
+}
+
+/// One request `fetch_data` made, as it was sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchedRequest {
+    pub url: String,
+    pub status: Option<u16>,
+    /// The raw response's file, relative to [`crate::OutputDirs::raw`], if a
+    /// body came back.
+    pub raw_file: Option<String>,
+}
+
+/// The requests made for each endpoint since they were last taken, so the
+/// manifest describes the URLs actually fetched rather than rebuilt ones.
+#[derive(Debug, Default)]
+pub struct RequestLog {
+    requests: Mutex<HashMap<Endpoint, Vec<FetchedRequest>>>,
+    disabled: bool,
+}
+
+impl RequestLog {
+    /// A log that keeps nothing, for modes that fetch until stopped and
+    /// never write a manifest to take the requests into.
+    pub fn disabled() -> Self {
+        RequestLog {
+            disabled: true,
+            ..RequestLog::default()
+        }
+    }
+
+    pub fn record(&self, endpoint: Endpoint, request: FetchedRequest) {
+        if self.disabled {
+            return;
+        }
+        self.requests
+            .lock()
+            .unwrap()
+            .entry(endpoint)
+            .or_default()
+            .push(request);
+    }
+
+    pub fn take(&self, endpoint: Endpoint) -> Vec<FetchedRequest> {
+        self.requests
+            .lock()
+            .unwrap()
+            .remove(&endpoint)
+            .unwrap_or_default()
+    }
+}
+
+impl FetchManifest {
+    /// Describes the files `report`'s endpoints left in `ctx.dirs`.
+    pub fn build(report: &FetchReport, ctx: &FetchContext) -> Result<Self, FetchError> {
+        let mut endpoints = Vec::new();
+        for entry in &report.endpoints {
+            let endpoint = entry.endpoint;
+            let requests = entry
+                .requests
+                .iter()
+                .map(|request| {
+                    Ok(ManifestRequest {
+                        url: request.url.clone(),
+                        params: query_params(&request.url),
+                        status: request.status,
+                        raw: match &request.raw_file {
+                            // Named relative to `OutputDirs::raw`.
+                            Some(file) => {
+                                FileDigest::of(&ctx.dirs.raw.join(file))?.map(|digest| FileDigest {
+                                    file: file.clone(),
+                                    ..digest
+                                })
+                            }
+                            None => None,
+                        },
+                    })
+                })
+                .collect::<io::Result<_>>()?;
+            let prepared_path = ctx
+                .dirs
+                .prepared
+                .join(endpoint.file_name())
+                .with_extension(endpoint.file_extension());
+            let prepared = FileDigest::of(&prepared_path)?;
+            let rows = match prepared {
+                Some(_) => count_rows(endpoint, &prepared_path)?,
+                None => None,
+            };
+            let (outcome, error) = match &entry.outcome {
+                FetchOutcome::Success => ("ok", None),
+                FetchOutcome::Skipped(_) => ("skipped", None),
+                FetchOutcome::Failed(e) => ("failed", Some(e.to_string())),
+            };
+
+            endpoints.push(ManifestEntry {
+                endpoint,
+                requests,
+                fetched_at: entry.started_at,
+                elapsed_ms: entry.elapsed.as_millis(),
+                outcome: outcome.to_string(),
+                error,
+                prepared,
+                rows,
+            });
+        }
+        Ok(FetchManifest {
+            generated_at: Utc::now(),
+            endpoints,
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+fn query_params(url: &str) -> BTreeMap<String, String> {
+    Url::parse(url)
+        .map(|url| url.query_pairs().into_owned().collect())
+        .unwrap_or_default()
+}
+
+fn count_rows(endpoint: Endpoint, path: &Path) -> Result<Option<usize>, FetchError> {
+    match endpoint.file_extension() {
+        "csv" => {
+            let mut reader = csv::Reader::from_path(path)?;
+            Ok(Some(reader.records().filter(Result::is_ok).count()))
+        }
+        // The schedule is a list of game dates; count the games in them.
+        _ => {
+            let value: Value = serde_json::from_slice(&fs::read(path)?)?;
+            Ok(value.as_array().map(|dates| {
+                dates
+                    .iter()
+                    .map(|date| date["games"].as_array().map_or(1, Vec::len))
+                    .sum()
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_server::{MockResponse, MockServer},
+        next_build::BuildIdCache,
+        pipeline::fetch_concurrently,
+        query::StatsQuery,
+        transport::FixtureTransport,
+        OutputDirs,
+    };
+    use std::{collections::HashSet, env, sync::Arc};
+
+    #[tokio::test]
+    async fn manifest_describes_each_endpoint() {
+        let dir = env::temp_dir().join("nba_fetch_manifest");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let transport = FixtureTransport::seed_data()
+            .with_file(Endpoint::InjuryReport, dir.join("missing.json"));
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(transport))
+            .with_odds_build(Arc::new(BuildIdCache::with_build_id("currentBuild")));
+
+        let endpoints = [
+            Endpoint::TeamsGeneralAdvanced,
+            Endpoint::SeasonSchedule,
+            Endpoint::Bet365Odds,
+            Endpoint::InjuryReport,
+        ];
+        let report = fetch_concurrently(&endpoints, &ctx, 2).await;
+        FetchManifest::build(&report, &ctx)
+            .unwrap()
+            .write(&dirs.manifest())
+            .unwrap();
+
+        let manifest = FetchManifest::read(&dir.join(MANIFEST_FILE)).unwrap();
+        let advanced = &manifest.endpoints[0];
+        assert_eq!(advanced.outcome, "ok");
+        assert_eq!(advanced.requests.len(), 1);
+        let request = &advanced.requests[0];
+        assert_eq!(request.params["MeasureType"], "Advanced");
+        // Fixtures are read without a request.
+        assert_eq!(request.status, None);
+        assert_eq!(advanced.rows, Some(30));
+        let raw = request.raw.as_ref().unwrap();
+        let raw_bytes = fs::read(dirs.raw.join(&raw.file)).unwrap();
+        assert_eq!(raw.bytes, raw_bytes.len() as u64);
+        assert_eq!(raw.sha256, format!("{:x}", Sha256::digest(&raw_bytes)));
+        assert_eq!(advanced.prepared.as_ref().unwrap().sha256.len(), 64);
+
+        assert!(manifest.endpoints[1].rows.unwrap() > 0);
+        assert!(manifest.endpoints[2].rows.unwrap() > 0);
+        // The odds page is recorded under the build it was fetched from.
+        let odds = &manifest.endpoints[2].requests[0];
+        assert!(odds.url.contains("/_next/data/currentBuild/"));
+        assert_ne!(odds.url, Endpoint::Bet365Odds.url(&StatsQuery::default()));
+
+        let injuries = &manifest.endpoints[3];
+        assert_eq!(injuries.outcome, "failed");
+        assert!(injuries.error.as_ref().unwrap().contains("missing.json"));
+        assert!(injuries.prepared.is_none() && injuries.rows.is_none());
+        assert!(injuries.requests[0].raw.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn manifest_records_the_requests_as_sent() {
+        let dir = env::temp_dir().join("nba_fetch_manifest_requests");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let seed = Endpoint::data_file_path().join("seed_data");
+        let server = MockServer::start().await;
+        server
+            .route(
+                "/stats/teamplayeronoffsummary",
+                vec![MockResponse::file(
+                    &seed.join("teamplayeronoffsummary.json"),
+                )],
+            )
+            .route("/stats/playerindex", vec![MockResponse::status(404)]);
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport());
+
+        let endpoints = [Endpoint::PlayerOnOff, Endpoint::PlayerIndex];
+        let report = fetch_concurrently(&endpoints, &ctx, 2).await;
+        let manifest = FetchManifest::build(&report, &ctx).unwrap();
+
+        let on_off = &manifest.endpoints[0];
+        assert_eq!(on_off.requests.len(), 30);
+        let team_ids: HashSet<&str> = on_off
+            .requests
+            .iter()
+            .map(|r| r.params["TeamID"].as_str())
+            .collect();
+        assert_eq!(team_ids.len(), 30);
+        assert!(!team_ids.contains("0"));
+        assert!(on_off.requests.iter().all(|r| r.status == Some(200)));
+        let raw = on_off.requests[0].raw.as_ref().unwrap();
+        assert!(raw.file.starts_with("fetched_PlayerOnOff_data_"));
+
+        let index = &manifest.endpoints[1];
+        assert_eq!(index.outcome, "failed");
+        assert_eq!(index.requests[0].status, Some(404));
+        assert!(index.requests[0].raw.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
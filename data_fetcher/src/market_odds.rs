@@ -0,0 +1,415 @@
+use crate::{
+    bet_ml_odds::write_bet_ml_odds, error::FetchError, next_build::fetch_odds_page,
+    result_sets::write_csv_with_header, Endpoint, FetchContext,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A betting market as sportsbookreview splits its pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum OddsMarket {
+    MoneyLine,
+    Spread,
+    Total,
+}
+
+impl OddsMarket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OddsMarket::MoneyLine => "money-line",
+            OddsMarket::Spread => "pointspread",
+            OddsMarket::Total => "totals",
+        }
+    }
+}
+
+/// The part of a game a line is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum OddsScope {
+    FullGame,
+    FirstHalf,
+    FirstQuarter,
+}
+
+impl OddsScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OddsScope::FullGame => "full-game",
+            OddsScope::FirstHalf => "1st-half",
+            OddsScope::FirstQuarter => "1st-quarter",
+        }
+    }
+}
+
+/// Every market in every scope, one page each.
+pub const ODDS_ENDPOINTS: [Endpoint; 9] = [
+    Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame),
+    Endpoint::Odds(OddsMarket::Spread, OddsScope::FullGame),
+    Endpoint::Odds(OddsMarket::Total, OddsScope::FullGame),
+    Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstHalf),
+    Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstHalf),
+    Endpoint::Odds(OddsMarket::Total, OddsScope::FirstHalf),
+    Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstQuarter),
+    Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstQuarter),
+    Endpoint::Odds(OddsMarket::Total, OddsScope::FirstQuarter),
+];
+
+/// The sportsbookreview data URL for one market and scope under the
+/// Next.js build `build_id`.
+pub(crate) fn odds_page_url(build_id: &str, market: OddsMarket, scope: OddsScope) -> String {
+    format!(
+        "https://www.sportsbookreview.com/_next/data/{}/betting-odds/nba-basketball/{}/{}.json?league=nba-basketball&oddsType={}&oddsScope={}",
+        build_id,
+        market.as_str(),
+        scope.as_str(),
+        market.as_str(),
+        scope.as_str()
+    )
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OddsPage {
+    pub(crate) page_props: OddsPageProps,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OddsPageProps {
+    pub(crate) odds_tables: Vec<OddsTable>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OddsTable {
+    pub(crate) odds_table_model: OddsTableModel,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OddsTableModel {
+    pub(crate) game_rows: Vec<GameRow>,
+    #[serde(default)]
+    pub(crate) sportsbooks: Vec<Sportsbook>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Sportsbook {
+    pub(crate) machine_name: String,
+    pub(crate) name: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GameRow {
+    pub(crate) game_view: GameView,
+    /// One per sportsbook; null where a book has no line for the game.
+    #[serde(default)]
+    pub(crate) odds_views: Vec<Option<OddsView>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GameView {
+    pub(crate) game_id: i64,
+    pub(crate) start_date: String,
+    pub(crate) away_team: OddsTeam,
+    pub(crate) home_team: OddsTeam,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OddsTeam {
+    pub(crate) full_name: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OddsView {
+    pub(crate) sportsbook: String,
+    pub(crate) opening_line: Option<Line>,
+    pub(crate) current_line: Option<Line>,
+}
+
+/// Every page uses the same line shape and leaves the other markets' fields
+/// null.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Line {
+    pub(crate) home_odds: Option<i64>,
+    pub(crate) away_odds: Option<i64>,
+    pub(crate) over_odds: Option<i64>,
+    pub(crate) under_odds: Option<i64>,
+    pub(crate) home_spread: Option<f64>,
+    pub(crate) away_spread: Option<f64>,
+    pub(crate) total: Option<f64>,
+}
+
+impl Line {
+    /// `(side, line, price)` for each side of `market`.
+    fn sides(&self, market: OddsMarket) -> [(&'static str, Option<f64>, Option<i64>); 2] {
+        match market {
+            OddsMarket::MoneyLine => [
+                ("home", None, self.home_odds),
+                ("away", None, self.away_odds),
+            ],
+            OddsMarket::Spread => [
+                ("home", self.home_spread, self.home_odds),
+                ("away", self.away_spread, self.away_odds),
+            ],
+            OddsMarket::Total => [
+                ("over", self.total, self.over_odds),
+                ("under", self.total, self.under_odds),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quote {
+    Opening,
+    Current,
+}
+
+/// One price: a side of a market at one sportsbook, as it opened or as it
+/// is now. `game_id` is sportsbookreview's, `line` is the spread or total
+/// (empty for moneylines) and `price` is in American odds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OddsRow {
+    pub game_id: i64,
+    pub start_date: String,
+    pub away_team: String,
+    pub home_team: String,
+    pub market: OddsMarket,
+    pub scope: OddsScope,
+    pub sportsbook: String,
+    pub side: String,
+    pub quote: Quote,
+    pub line: Option<f64>,
+    pub price: i64,
+}
+
+impl OddsRow {
+    /// The fields, as the header of every prepared odds file.
+    pub const COLUMNS: [&'static str; 11] = [
+        "game_id",
+        "start_date",
+        "away_team",
+        "home_team",
+        "market",
+        "scope",
+        "sportsbook",
+        "side",
+        "quote",
+        "line",
+        "price",
+    ];
+}
+
+/// Flattens one page into [`OddsRow`]s. Books without a line and sides
+/// without a price are left out rather than failing the page.
+fn odds_rows(page: &OddsPage, market: OddsMarket, scope: OddsScope) -> Vec<OddsRow> {
+    let mut rows = Vec::new();
+    for table in &page.page_props.odds_tables {
+        let model = &table.odds_table_model;
+        let book_names: HashMap<&str, &str> = model
+            .sportsbooks
+            .iter()
+            .map(|b| (b.machine_name.as_str(), b.name.as_str()))
+            .collect();
+        for game in &model.game_rows {
+            let view = &game.game_view;
+            for odds in game.odds_views.iter().flatten() {
+                let sportsbook = book_names
+                    .get(odds.sportsbook.as_str())
+                    .copied()
+                    .unwrap_or(&odds.sportsbook);
+                for (quote, line) in [
+                    (Quote::Opening, &odds.opening_line),
+                    (Quote::Current, &odds.current_line),
+                ] {
+                    let Some(line) = line else { continue };
+                    for (side, value, price) in line.sides(market) {
+                        let Some(price) = price else { continue };
+                        rows.push(OddsRow {
+                            game_id: view.game_id,
+                            start_date: view.start_date.clone(),
+                            away_team: view.away_team.full_name.clone(),
+                            home_team: view.home_team.full_name.clone(),
+                            market,
+                            scope,
+                            sportsbook: sportsbook.to_string(),
+                            side: side.to_string(),
+                            quote,
+                            line: value,
+                            price,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// The market and scope `endpoint` is a page of.
+fn odds_page_of(endpoint: Endpoint) -> Result<(OddsMarket, OddsScope), FetchError> {
+    match endpoint {
+        Endpoint::Odds(market, scope) => Ok((market, scope)),
+        other => Err(FetchError::NotAnOddsEndpoint(other)),
+    }
+}
+
+/// Fetches one market and scope as [`OddsRow`]s, keeping the raw page.
+pub async fn fetch_odds_rows(
+    endpoint: Endpoint,
+    ctx: &FetchContext,
+) -> Result<Vec<OddsRow>, FetchError> {
+    let (market, scope) = odds_page_of(endpoint)?;
+    // Fetch Data
+    let page: OddsPage = fetch_odds_page(ctx, endpoint, market, scope).await?;
+    Ok(odds_rows(&page, market, scope))
+}
+
+/// Fetches one market and scope and writes its [`OddsRow`]s. Every page
+/// writes the same columns, so the prepared files stack into one table.
+///
+/// The full-game moneyline page is the one [`Endpoint::Bet365Odds`] reads
+/// too, so its bet365 file is written from the same page rather than
+/// downloading it twice.
+pub async fn fetch_market_odds(endpoint: Endpoint, ctx: &FetchContext) -> Result<(), FetchError> {
+    let (market, scope) = odds_page_of(endpoint)?;
+    // Fetch Data
+    let page: OddsPage = fetch_odds_page(ctx, endpoint, market, scope).await?;
+    // File Name for future reference
+    let mut save_file_path = ctx.dirs.prepared.join(endpoint.file_name());
+    save_file_path.set_extension("csv");
+
+    write_csv_with_header(
+        &OddsRow::COLUMNS,
+        odds_rows(&page, market, scope),
+        save_file_path,
+    )?;
+
+    if (market, scope) == (OddsMarket::MoneyLine, OddsScope::FullGame) {
+        write_bet_ml_odds(&page, &ctx.dirs)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport, OutputDirs};
+    use serde_json::Value;
+    use std::{env, fs, sync::Arc};
+
+    #[test]
+    fn moneyline_full_game_is_the_bet365_page() {
+        let url =
+            Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame).url(&StatsQuery::default());
+        assert_eq!(url, Endpoint::Bet365Odds.url(&StatsQuery::default()));
+        assert!(
+            odds_page_url("build", OddsMarket::Total, OddsScope::FirstQuarter)
+                .contains("/_next/data/build/betting-odds/nba-basketball/totals/1st-quarter.json?")
+        );
+    }
+
+    #[tokio::test]
+    async fn every_book_market_and_scope_is_one_long_table() {
+        let dir = env::temp_dir().join("nba_market_odds");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        let mut rows: Vec<OddsRow> = Vec::new();
+        for endpoint in ODDS_ENDPOINTS {
+            fetch_market_odds(endpoint, &ctx).await.unwrap();
+            let path = dirs
+                .prepared
+                .join(endpoint.file_name())
+                .with_extension("csv");
+            let mut reader = csv::Reader::from_path(path).unwrap();
+            assert_eq!(reader.headers().unwrap(), &OddsRow::COLUMNS[..]);
+            rows.extend(reader.deserialize().map(Result::unwrap));
+        }
+        // The moneyline page is bet365's too.
+        assert!(dirs.prepared.join("fetched_Bet365Odds_data.csv").exists());
+
+        // 5 games, 6 books, 2 sides, opening and current.
+        let full_moneyline: Vec<&OddsRow> = rows
+            .iter()
+            .filter(|r| r.market == OddsMarket::MoneyLine && r.scope == OddsScope::FullGame)
+            .collect();
+        assert_eq!(full_moneyline.len(), 120);
+        let first = full_moneyline[0];
+        assert_eq!(
+            (first.sportsbook.as_str(), first.side.as_str(), first.quote),
+            ("BetMGM", "home", Quote::Opening)
+        );
+        assert_eq!((first.line, first.price), (None, 100));
+        assert_eq!(first.home_team, "San Antonio Spurs");
+
+        // A book with no line for a game is left out...
+        let spreads = rows
+            .iter()
+            .filter(|r| r.market == OddsMarket::Spread && r.scope == OddsScope::FullGame);
+        assert_eq!(spreads.clone().count(), 116);
+        let spurs = spreads
+            .clone()
+            .find(|r| r.game_id == 315632 && r.side == "home" && r.quote == Quote::Opening)
+            .unwrap();
+        assert_eq!(spurs.line, Some(-1.5));
+        // ...and so is a quote that was pulled.
+        let totals = rows
+            .iter()
+            .filter(|r| r.market == OddsMarket::Total && r.scope == OddsScope::FullGame);
+        assert_eq!(totals.clone().count(), 118);
+        assert!(totals
+            .filter(|r| r.side == "over")
+            .all(|r| r.line.is_some()));
+
+        let books: std::collections::BTreeSet<&str> =
+            rows.iter().map(|r| r.sportsbook.as_str()).collect();
+        assert!(books.contains("BetRivers") && books.contains("bet365"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_page_without_games_still_has_columns() {
+        let dir = env::temp_dir().join("nba_market_odds_empty");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let endpoint = Endpoint::Odds(OddsMarket::Spread, OddsScope::FullGame);
+        let seed = Endpoint::data_file_path()
+            .join("seed_data")
+            .join("pointSpreadFullGame.json");
+        let mut page: Value = serde_json::from_slice(&fs::read(seed).unwrap()).unwrap();
+        *page
+            .pointer_mut("/pageProps/oddsTables/0/oddsTableModel/gameRows")
+            .unwrap() = Value::Array(Vec::new());
+        let empty = dir.join("empty.json");
+        fs::write(&empty, page.to_string()).unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone()).with_transport(Arc::new(
+            FixtureTransport::seed_data().with_file(endpoint, empty),
+        ));
+
+        fetch_market_odds(endpoint, &ctx).await.unwrap();
+
+        let path = dirs
+            .prepared
+            .join(endpoint.file_name())
+            .with_extension("csv");
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        assert_eq!(reader.headers().unwrap(), &OddsRow::COLUMNS[..]);
+        assert_eq!(reader.records().count(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+//! A minimal HTTP/1.1 stand-in for stats.nba.com and friends, used by tests
+//! to exercise the real network path: retries, timeouts, gzip handling and
+//! the files fetchers write.
+
+use crate::{
+    client::{ClientConfig, HttpClient},
+    transport::{FixtureTransport, RedirectTransport, Transport, TransportFuture},
+    Endpoint,
+};
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::sleep,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    status: u16,
+    body: Vec<u8>,
+    gzip: bool,
+    delay: Duration,
+    truncate: bool,
+    headers: Vec<(String, String)>,
+}
+
+impl MockResponse {
+    pub(crate) fn ok(body: impl Into<Vec<u8>>) -> Self {
+        MockResponse {
+            status: 200,
+            body: body.into(),
+            gzip: false,
+            delay: Duration::ZERO,
+            truncate: false,
+            headers: Vec::new(),
+        }
+    }
+
+    /// A `200` serving the contents of a file, e.g. from `data/seed_data`.
+    pub(crate) fn file(path: &Path) -> Self {
+        Self::ok(std::fs::read(path).expect("fixture file exists"))
+    }
+
+    pub(crate) fn status(status: u16) -> Self {
+        MockResponse {
+            status,
+            ..Self::ok(format!("{{\"error\":{}}}", status))
+        }
+    }
+
+    pub(crate) fn not_modified() -> Self {
+        MockResponse {
+            status: 304,
+            ..Self::ok(Vec::new())
+        }
+    }
+
+    pub(crate) fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub(crate) fn gzipped(mut self) -> Self {
+        self.gzip = true;
+        self
+    }
+
+    /// Waits before sending anything, to trip the client's read timeout.
+    pub(crate) fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sends half the body and hangs up while promising all of it.
+    pub(crate) fn truncated(mut self) -> Self {
+        self.truncate = true;
+        self
+    }
+
+    fn encoded_body(&self) -> Vec<u8> {
+        if !self.gzip {
+            return self.body.clone();
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&self.body).unwrap();
+        encoder.finish().unwrap()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Route {
+    matcher: String,
+    /// Served in order; the last response repeats once the others are used.
+    responses: Vec<MockResponse>,
+    hits: usize,
+    /// Request line and headers of every request served.
+    requests: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockServer {
+    origin: String,
+    routes: Arc<Mutex<Vec<Route>>>,
+}
+
+impl MockServer {
+    pub(crate) async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        let server = MockServer {
+            origin,
+            routes: Arc::new(Mutex::new(Vec::new())),
+        };
+
+        let routes = Arc::clone(&server.routes);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, Arc::clone(&routes)));
+            }
+        });
+        server
+    }
+
+    pub(crate) fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Serves `responses` to requests whose path and query contain `matcher`.
+    /// Routes are tried in the order they were added.
+    pub(crate) fn route(&self, matcher: &str, responses: Vec<MockResponse>) -> &Self {
+        self.routes.lock().unwrap().push(Route {
+            matcher: matcher.to_string(),
+            responses,
+            hits: 0,
+            requests: Vec::new(),
+        });
+        self
+    }
+
+    pub(crate) fn hits(&self, matcher: &str) -> usize {
+        self.routes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.matcher == matcher)
+            .map(|r| r.hits)
+            .sum()
+    }
+
+    pub(crate) fn requests(&self, matcher: &str) -> Vec<String> {
+        self.routes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.matcher == matcher)
+            .flat_map(|r| r.requests.clone())
+            .collect()
+    }
+
+    /// A transport pointed at this server with short timeouts and backoff.
+    pub(crate) fn transport(&self) -> Arc<dyn Transport> {
+        Arc::new(RedirectTransport::new(self.origin(), test_client()))
+    }
+}
+
+/// Serves the seed data, except that asking for its endpoint panics.
+#[derive(Debug)]
+pub(crate) struct PanicsOn(pub Endpoint, pub FixtureTransport);
+
+impl Transport for PanicsOn {
+    fn get<'a>(&'a self, endpoint: Endpoint, url: &'a str) -> TransportFuture<'a> {
+        if endpoint == self.0 {
+            panic!("no transport for {:?}", endpoint);
+        }
+        self.1.get(endpoint, url)
+    }
+}
+
+pub(crate) fn test_client() -> HttpClient {
+    HttpClient::new(ClientConfig {
+        connect_timeout: Duration::from_millis(500),
+        read_timeout: Duration::from_millis(300),
+        max_retries: 2,
+        base_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        requests_per_second: 1000.0,
+        burst: 100,
+    })
+}
+
+async fn handle(mut stream: TcpStream, routes: Arc<Mutex<Vec<Route>>>) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let target = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        routes
+            .iter_mut()
+            .find(|r| target.contains(&r.matcher))
+            .map(|route| {
+                let index = route.hits.min(route.responses.len() - 1);
+                route.hits += 1;
+                route.requests.push(request.to_string());
+                route.responses[index].clone()
+            })
+    }
+    .unwrap_or_else(|| MockResponse::status(404));
+
+    sleep(response.delay).await;
+
+    let body = response.encoded_body();
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        body.len()
+    );
+    if response.gzip {
+        head.push_str("Content-Encoding: gzip\r\n");
+    }
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let sent = if response.truncate {
+        &body[..body.len() / 2]
+    } else {
+        &body[..]
+    };
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(sent).await;
+    let _ = stream.shutdown().await;
+}
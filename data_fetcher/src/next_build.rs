@@ -0,0 +1,247 @@
+use crate::{
+    decompress_or_convert,
+    error::FetchError,
+    fetch_data,
+    market_odds::{odds_page_url, OddsMarket, OddsScope},
+    Endpoint, FetchContext,
+};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// The sportsbookreview page the odds data URLs belong to. Its
+/// `__NEXT_DATA__` payload names the Next.js build they are served under.
+pub const ODDS_LANDING_URL: &str = "https://www.sportsbookreview.com/betting-odds/nba-basketball/";
+
+/// The last build ID known to work, used when the landing page can't be
+/// fetched or doesn't name one.
+pub const FALLBACK_BUILD_ID: &str = "z65WyQ2UMs83FwnSdRynY";
+
+/// The `buildId` in a Next.js page's `__NEXT_DATA__` script, if it has one.
+pub fn build_id_from_html(html: &str) -> Option<String> {
+    let start = html.find("id=\"__NEXT_DATA__\"")?;
+    let body = &html[start..];
+    let body = &body[body.find('>')? + 1..];
+    let payload = &body[..body.find("</script>")?];
+    let data: Value = serde_json::from_str(payload).ok()?;
+    data.get("buildId")?.as_str().map(str::to_string)
+}
+
+/// The sportsbookreview build ID, resolved from the landing page on first
+/// use and kept until a data URL built with it stops resolving.
+#[derive(Debug, Default)]
+pub struct BuildIdCache {
+    current: Mutex<Option<String>>,
+}
+
+impl BuildIdCache {
+    /// A cache that starts out with `build_id` instead of resolving one.
+    pub fn with_build_id(build_id: &str) -> Self {
+        BuildIdCache {
+            current: Mutex::new(Some(build_id.to_string())),
+        }
+    }
+
+    /// The cached build ID, resolving it first if there is none. Falls back
+    /// to [`FALLBACK_BUILD_ID`] if the landing page doesn't give one.
+    pub async fn current(&self, ctx: &FetchContext) -> String {
+        let mut current = self.current.lock().await;
+        if let Some(build_id) = current.as_ref() {
+            return build_id.clone();
+        }
+        let build_id = resolve_build_id(ctx).await.unwrap_or_else(|e| {
+            warn!(
+                "Could not resolve the odds build ID ({}), using {}",
+                e, FALLBACK_BUILD_ID
+            );
+            FALLBACK_BUILD_ID.to_string()
+        });
+        *current = Some(build_id.clone());
+        build_id
+    }
+
+    /// Resolves the build ID again after `stale` stopped working. Returns
+    /// the new one, or `None` if the landing page still names `stale` or
+    /// can't be read. Concurrent callers with the same `stale` ID share one
+    /// lookup.
+    pub async fn refresh(&self, ctx: &FetchContext, stale: &str) -> Option<String> {
+        let mut current = self.current.lock().await;
+        if let Some(build_id) = current.as_ref().filter(|id| *id != stale) {
+            return Some(build_id.clone());
+        }
+        match resolve_build_id(ctx).await {
+            Ok(build_id) if build_id != stale => {
+                info!("Odds build ID changed from {} to {}", stale, build_id);
+                *current = Some(build_id.clone());
+                Some(build_id)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Could not re-resolve the odds build ID: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Fetches the landing page and reads the build ID out of it.
+async fn resolve_build_id(ctx: &FetchContext) -> Result<String, FetchError> {
+    let endpoint = Endpoint::OddsLandingPage;
+    let response = ctx.transport.get(endpoint, ODDS_LANDING_URL).await?;
+    let html = decompress_or_convert(&response.body, endpoint.file_name(), &ctx.dirs.raw)?;
+    build_id_from_html(&html).ok_or_else(|| FetchError::UnexpectedShape {
+        endpoint,
+        pointer: "__NEXT_DATA__/buildId".to_string(),
+    })
+}
+
+/// Resolves the current build ID into the context's cache, replacing
+/// whatever was there.
+pub async fn fetch_odds_build_id(ctx: &FetchContext) -> Result<(), FetchError> {
+    let build_id = resolve_build_id(ctx).await?;
+    info!("Odds build ID is {}", build_id);
+    *ctx.odds_build.current.lock().await = Some(build_id);
+    Ok(())
+}
+
+/// Fetches the data page for `market` and `scope` under the current build.
+/// A 404 means the site was redeployed, so the build ID is resolved again
+/// and the page retried once under the new one.
+pub(crate) async fn fetch_odds_page<T>(
+    ctx: &FetchContext,
+    endpoint: Endpoint,
+    market: OddsMarket,
+    scope: OddsScope,
+) -> Result<T, FetchError>
+where
+    T: DeserializeOwned,
+{
+    let build_id = ctx.odds_build.current(ctx).await;
+    let url = odds_page_url(&build_id, market, scope);
+    match fetch_data(ctx, endpoint, &url, endpoint.file_name()).await {
+        Err(FetchError::PermanentFailure(StatusCode::NOT_FOUND)) => {
+            match ctx.odds_build.refresh(ctx, &build_id).await {
+                Some(build_id) => {
+                    let url = odds_page_url(&build_id, market, scope);
+                    fetch_data(ctx, endpoint, &url, endpoint.file_name()).await
+                }
+                None => Err(FetchError::PermanentFailure(StatusCode::NOT_FOUND)),
+            }
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        market_odds::fetch_odds_rows,
+        mock_server::{MockResponse, MockServer},
+        query::StatsQuery,
+        transport::FixtureTransport,
+        OutputDirs,
+    };
+    use std::{env, fs, path::PathBuf, sync::Arc};
+
+    /// The saved landing page names this build.
+    const SEED_BUILD_ID: &str = "Kq7XbN2mRt4WcLp9VfHdE";
+
+    fn seed_file(name: &str) -> PathBuf {
+        Endpoint::data_file_path().join("seed_data").join(name)
+    }
+
+    fn temp_dirs(name: &str) -> OutputDirs {
+        let dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        dirs
+    }
+
+    #[test]
+    fn build_id_comes_from_next_data() {
+        let html = fs::read_to_string(seed_file("bettingOddsNbaBasketball.html")).unwrap();
+        assert_eq!(build_id_from_html(&html).as_deref(), Some(SEED_BUILD_ID));
+
+        assert_eq!(
+            build_id_from_html("<html><body>Access denied</body></html>"),
+            None
+        );
+        let truncated = &html[..html.find("\"buildId\"").unwrap()];
+        assert_eq!(build_id_from_html(truncated), None);
+    }
+
+    #[tokio::test]
+    async fn odds_fetches_resolve_the_build_once() {
+        let dirs = temp_dirs("nba_next_build_seed");
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        let endpoint = Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame);
+        assert!(!fetch_odds_rows(endpoint, &ctx).await.unwrap().is_empty());
+        assert_eq!(ctx.odds_build.current(&ctx).await, SEED_BUILD_ID);
+        // The landing page is kept like any other raw response.
+        assert!(dirs.raw.join("fetched_OddsLandingPage_data.txt").exists());
+
+        fs::remove_dir_all(dirs.raw.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_404_re_resolves_the_build() {
+        let dirs = temp_dirs("nba_next_build_redeploy");
+        let server = MockServer::start().await;
+        server
+            .route("/_next/data/oldBuild/", vec![MockResponse::status(404)])
+            .route(
+                &format!("/_next/data/{}/", SEED_BUILD_ID),
+                vec![MockResponse::file(&seed_file("moneyLineFullGame.json"))],
+            )
+            .route(
+                "/betting-odds/nba-basketball/",
+                vec![MockResponse::file(&seed_file(
+                    "bettingOddsNbaBasketball.html",
+                ))],
+            );
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport())
+            .with_odds_build(Arc::new(BuildIdCache::with_build_id("oldBuild")));
+
+        let endpoint = Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame);
+        let rows = fetch_odds_rows(endpoint, &ctx).await.unwrap();
+        assert_eq!(rows.len(), 120);
+        assert_eq!(server.hits("/_next/data/oldBuild/"), 1);
+        assert_eq!(server.hits("/betting-odds/nba-basketball/"), 1);
+        // Later fetches go straight to the new build.
+        fetch_odds_rows(endpoint, &ctx).await.unwrap();
+        assert_eq!(server.hits(&format!("/_next/data/{}/", SEED_BUILD_ID)), 2);
+        assert_eq!(server.hits("/betting-odds/nba-basketball/"), 1);
+
+        fs::remove_dir_all(dirs.raw.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_landing_page_falls_back() {
+        let dirs = temp_dirs("nba_next_build_fallback");
+        let server = MockServer::start().await;
+        server
+            .route(
+                &format!("/_next/data/{}/", FALLBACK_BUILD_ID),
+                vec![MockResponse::file(&seed_file("moneyLineFullGame.json"))],
+            )
+            .route(
+                "/betting-odds/nba-basketball/",
+                vec![MockResponse::ok("<html><body>Access denied</body></html>")],
+            );
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport());
+
+        let endpoint = Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame);
+        fetch_odds_rows(endpoint, &ctx).await.unwrap();
+        assert_eq!(ctx.odds_build.current(&ctx).await, FALLBACK_BUILD_ID);
+
+        fs::remove_dir_all(dirs.raw.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{
+    error::FetchError,
+    market_odds::{fetch_odds_rows, OddsMarket, OddsRow, OddsScope, Quote, ODDS_ENDPOINTS},
+    Endpoint, FetchContext,
+};
+use chrono::{DateTime, Duration as TimeDelta, Utc};
+use csv::WriterBuilder;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::{self, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+pub fn odds_history_file_path() -> PathBuf {
+    // "../data/odds_history/odds_snapshots.csv"
+    Endpoint::data_file_path()
+        .join("odds_history")
+        .join("odds_snapshots.csv")
+}
+
+/// A current price as it stood at `captured_at`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OddsSnapshot {
+    pub captured_at: DateTime<Utc>,
+    pub game_id: i64,
+    pub start_date: String,
+    pub away_team: String,
+    pub home_team: String,
+    pub market: OddsMarket,
+    pub scope: OddsScope,
+    pub sportsbook: String,
+    pub side: String,
+    pub line: Option<f64>,
+    pub price: i64,
+}
+
+impl OddsSnapshot {
+    pub fn key(&self) -> MarketKey {
+        MarketKey {
+            game_id: self.game_id,
+            market: self.market,
+            scope: self.scope,
+            sportsbook: self.sportsbook.clone(),
+            side: self.side.clone(),
+        }
+    }
+
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.start_date)
+            .ok()
+            .map(|d| d.with_timezone(&Utc))
+    }
+}
+
+/// One side of one market at one book: what a line-movement series follows.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarketKey {
+    pub game_id: i64,
+    pub market: OddsMarket,
+    pub scope: OddsScope,
+    pub sportsbook: String,
+    pub side: String,
+}
+
+/// An append-only CSV of [`OddsSnapshot`]s. A price is only appended when
+/// it differs from the last one stored for its [`MarketKey`], so each row is
+/// a line move (or a first sighting).
+#[derive(Debug)]
+pub struct OddsHistory {
+    path: PathBuf,
+    latest: HashMap<MarketKey, (Option<f64>, i64)>,
+}
+
+impl OddsHistory {
+    /// Opens the store at `path`, creating it on the first append.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, FetchError> {
+        let path = path.into();
+        let mut latest = HashMap::new();
+        for snapshot in read_snapshots(&path)? {
+            latest.insert(snapshot.key(), (snapshot.line, snapshot.price));
+        }
+        Ok(OddsHistory { path, latest })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends the current quotes in `rows` that moved since they were last
+    /// stored, and returns how many that was.
+    pub fn record(
+        &mut self,
+        captured_at: DateTime<Utc>,
+        rows: &[OddsRow],
+    ) -> Result<usize, FetchError> {
+        let mut moved = Vec::new();
+        for row in rows.iter().filter(|r| r.quote == Quote::Current) {
+            let snapshot = OddsSnapshot {
+                captured_at,
+                game_id: row.game_id,
+                start_date: row.start_date.clone(),
+                away_team: row.away_team.clone(),
+                home_team: row.home_team.clone(),
+                market: row.market,
+                scope: row.scope,
+                sportsbook: row.sportsbook.clone(),
+                side: row.side.clone(),
+                line: row.line,
+                price: row.price,
+            };
+            let quote = (snapshot.line, snapshot.price);
+            if self.latest.insert(snapshot.key(), quote) != Some(quote) {
+                moved.push(snapshot);
+            }
+        }
+        if moved.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let is_new = fs::metadata(&self.path).map_or(true, |m| m.len() == 0);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut wtr = WriterBuilder::new().has_headers(is_new).from_writer(file);
+        for snapshot in &moved {
+            wtr.serialize(snapshot)?;
+        }
+        wtr.flush()?;
+        Ok(moved.len())
+    }
+}
+
+/// Every snapshot in the store at `path`, oldest first; none if it doesn't
+/// exist yet.
+pub fn read_snapshots(path: &Path) -> Result<Vec<OddsSnapshot>, FetchError> {
+    let mut reader = match csv::Reader::from_path(path) {
+        Ok(reader) => reader,
+        Err(e) => match e.kind() {
+            csv::ErrorKind::Io(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new())
+            }
+            _ => return Err(e.into()),
+        },
+    };
+    let mut snapshots = reader
+        .deserialize()
+        .collect::<Result<Vec<OddsSnapshot>, _>>()?;
+    snapshots.sort_by_key(|s| s.captured_at);
+    Ok(snapshots)
+}
+
+/// Each key's snapshots in the order they were captured.
+pub fn line_movements(snapshots: &[OddsSnapshot]) -> BTreeMap<MarketKey, Vec<OddsSnapshot>> {
+    let mut series: BTreeMap<MarketKey, Vec<OddsSnapshot>> = BTreeMap::new();
+    for snapshot in snapshots {
+        series
+            .entry(snapshot.key())
+            .or_default()
+            .push(snapshot.clone());
+    }
+    for moves in series.values_mut() {
+        moves.sort_by_key(|s| s.captured_at);
+    }
+    series
+}
+
+/// The last price in `series` captured before its game started.
+pub fn closing_line(series: &[OddsSnapshot]) -> Option<&OddsSnapshot> {
+    series
+        .iter()
+        .filter(|s| s.start().is_none_or(|start| s.captured_at <= start))
+        .max_by_key(|s| s.captured_at)
+}
+
+/// The win probability an American price implies, vig included.
+pub fn implied_probability(price: i64) -> f64 {
+    let price = price as f64;
+    if price < 0.0 {
+        -price / (-price + 100.0)
+    } else {
+        100.0 / (price + 100.0)
+    }
+}
+
+/// How much better a bet at `taken` was than the `closing` price, in implied
+/// probability. Positive means the pick beat the close.
+pub fn closing_line_value(taken: i64, closing: i64) -> f64 {
+    implied_probability(closing) - implied_probability(taken)
+}
+
+/// What counts as steam: at least `min_books` books moving toward the same
+/// side within `window`, each by at least `min_points` of line or, where the
+/// line held, `min_probability` of implied probability.
+#[derive(Debug, Clone)]
+pub struct SteamConfig {
+    pub window: TimeDelta,
+    pub min_books: usize,
+    pub min_points: f64,
+    pub min_probability: f64,
+}
+
+impl Default for SteamConfig {
+    fn default() -> Self {
+        SteamConfig {
+            window: TimeDelta::minutes(10),
+            min_books: 3,
+            min_points: 0.5,
+            min_probability: 0.02,
+        }
+    }
+}
+
+/// Several books moving the same way at once.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SteamMove {
+    pub game_id: i64,
+    pub market: OddsMarket,
+    pub scope: OddsScope,
+    /// The side the money went to.
+    pub side: String,
+    /// When the last book in the move followed.
+    pub at: DateTime<Utc>,
+    pub books: Vec<String>,
+}
+
+/// One side of one market, across books: `(game, market, scope, side)`.
+type SideKey = (i64, OddsMarket, OddsScope, String);
+
+/// Whether the move from `before` to `after` went toward `after.side` by
+/// at least `config`'s thresholds. Laying more points, a higher total for
+/// the over (lower for the under) or a shorter price all count.
+fn move_toward_side(before: &OddsSnapshot, after: &OddsSnapshot, config: &SteamConfig) -> bool {
+    if let (Some(old), Some(new)) = (before.line, after.line) {
+        if old != new {
+            let toward = match (after.market, after.side.as_str()) {
+                (OddsMarket::Total, "under") => old - new,
+                (OddsMarket::Total, _) => new - old,
+                _ => old - new,
+            };
+            return toward >= config.min_points;
+        }
+    }
+    implied_probability(after.price) - implied_probability(before.price) >= config.min_probability
+}
+
+/// Finds steam in `snapshots`, ordered by time. Each book's moves are
+/// compared against its own previous snapshot.
+pub fn detect_steam_moves(snapshots: &[OddsSnapshot], config: &SteamConfig) -> Vec<SteamMove> {
+    // The time and book of every qualifying move, by side.
+    let mut moves: BTreeMap<SideKey, Vec<(DateTime<Utc>, String)>> = BTreeMap::new();
+    for (key, series) in line_movements(snapshots) {
+        for pair in series.windows(2) {
+            if move_toward_side(&pair[0], &pair[1], config) {
+                moves
+                    .entry((key.game_id, key.market, key.scope, key.side.clone()))
+                    .or_default()
+                    .push((pair[1].captured_at, key.sportsbook.clone()));
+            }
+        }
+    }
+
+    let mut steam = Vec::new();
+    for ((game_id, market, scope, side), mut moves) in moves {
+        moves.sort();
+        let mut start = 0;
+        for end in 0..moves.len() {
+            while moves[end].0 - moves[start].0 > config.window {
+                start += 1;
+            }
+            let books: BTreeSet<&String> = moves[start..=end].iter().map(|(_, b)| b).collect();
+            if books.len() >= config.min_books {
+                steam.push(SteamMove {
+                    game_id,
+                    market,
+                    scope,
+                    side: side.clone(),
+                    at: moves[end].0,
+                    books: books.into_iter().cloned().collect(),
+                });
+                // The same books don't make a second move.
+                start = end + 1;
+            }
+        }
+    }
+    steam.sort_by_key(|s| s.at);
+    steam
+}
+
+/// Fetches every market and scope once and records what moved. A page that
+/// fails is logged and skipped so the others are still captured.
+pub async fn capture_odds(
+    ctx: &FetchContext,
+    history: &mut OddsHistory,
+) -> Result<usize, FetchError> {
+    let captured_at = Utc::now();
+    let mut rows = Vec::new();
+    for endpoint in ODDS_ENDPOINTS {
+        match fetch_odds_rows(endpoint, ctx).await {
+            Ok(page) => rows.extend(page),
+            Err(e) => warn!("Odds capture of {:?} failed: {}", endpoint, e),
+        }
+    }
+    history.record(captured_at, &rows)
+}
+
+/// Captures odds every `interval` until stopped. No manifest is written,
+/// so the requests aren't kept.
+pub async fn run_odds_capture(
+    ctx: FetchContext,
+    mut history: OddsHistory,
+    interval: Duration,
+) -> Result<(), FetchError> {
+    let ctx = ctx.without_request_log();
+    loop {
+        let moved = capture_odds(&ctx, &mut history).await?;
+        info!("Recorded {} odds moves in {:?}", moved, history.path());
+        sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport, OutputDirs};
+    use chrono::TimeZone;
+    use std::{env, sync::Arc};
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 11, 11, 23, minute, 0).unwrap()
+    }
+
+    fn snapshot(minute: u32, book: &str, line: f64, price: i64) -> OddsSnapshot {
+        OddsSnapshot {
+            captured_at: at(minute),
+            game_id: 315632,
+            start_date: "2024-11-12T01:00:00+00:00".to_string(),
+            away_team: "Sacramento Kings".to_string(),
+            home_team: "San Antonio Spurs".to_string(),
+            market: OddsMarket::Spread,
+            scope: OddsScope::FullGame,
+            sportsbook: book.to_string(),
+            side: "home".to_string(),
+            line: Some(line),
+            price,
+        }
+    }
+
+    #[test]
+    fn clv_is_measured_in_implied_probability() {
+        assert!((implied_probability(-110) - 0.5238).abs() < 1e-4);
+        assert!((implied_probability(150) - 0.4).abs() < 1e-9);
+        // Took +150, closed +120: the pick beat the close.
+        assert!(closing_line_value(150, 120) > 0.0);
+        assert!(closing_line_value(-110, -105) < 0.0);
+    }
+
+    #[test]
+    fn steam_needs_enough_books_inside_the_window() {
+        let mut snapshots = Vec::new();
+        for book in ["BetMGM", "FanDuel", "Caesars", "DraftKings"] {
+            snapshots.push(snapshot(0, book, -1.5, -110));
+        }
+        // Three books lay another point within four minutes...
+        snapshots.push(snapshot(20, "BetMGM", -2.5, -110));
+        snapshots.push(snapshot(22, "FanDuel", -2.5, -110));
+        snapshots.push(snapshot(24, "Caesars", -2.5, -112));
+        // ...and a fourth follows much later, alone.
+        snapshots.push(snapshot(50, "DraftKings", -2.5, -110));
+
+        let steam = detect_steam_moves(&snapshots, &SteamConfig::default());
+        assert_eq!(steam.len(), 1);
+        assert_eq!(steam[0].at, at(24));
+        assert_eq!(steam[0].books, ["BetMGM", "Caesars", "FanDuel"]);
+
+        // Money the other way isn't steam toward this side.
+        let away: Vec<_> = snapshots
+            .iter()
+            .map(|s| OddsSnapshot {
+                line: s.line.map(|l| -l),
+                side: "away".to_string(),
+                ..s.clone()
+            })
+            .collect();
+        assert!(detect_steam_moves(&away, &SteamConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn closing_line_is_the_last_price_before_tip_off() {
+        let mut late = snapshot(0, "BetMGM", -4.0, -110);
+        late.captured_at = Utc.with_ymd_and_hms(2024, 11, 12, 1, 30, 0).unwrap();
+        let series = vec![
+            snapshot(0, "BetMGM", -1.5, -110),
+            snapshot(40, "BetMGM", -2.5, -110),
+            late,
+        ];
+        assert_eq!(closing_line(&series).unwrap().line, Some(-2.5));
+    }
+
+    #[tokio::test]
+    async fn only_moved_prices_are_appended() {
+        let dir = env::temp_dir().join("nba_odds_history");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("odds_snapshots.csv");
+        let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::under(&dir))
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        let mut history = OddsHistory::open(&path).unwrap();
+        let first = capture_odds(&ctx, &mut history).await.unwrap();
+        assert!(first > 0);
+        assert_eq!(capture_odds(&ctx, &mut history).await.unwrap(), 0);
+        assert_eq!(ctx.requests.take(ODDS_ENDPOINTS[0]).len(), 2);
+
+        // Capturing without a request log keeps nothing between captures.
+        let quiet = ctx.clone().without_request_log();
+        capture_odds(&quiet, &mut history).await.unwrap();
+        assert!(ODDS_ENDPOINTS
+            .iter()
+            .all(|&endpoint| quiet.requests.take(endpoint).is_empty()));
+
+        // A reopened store remembers where every price stood.
+        let mut history = OddsHistory::open(&path).unwrap();
+        let endpoint = ODDS_ENDPOINTS[0];
+        let mut rows = fetch_odds_rows(endpoint, &ctx).await.unwrap();
+        rows.retain(|r| r.quote == Quote::Current);
+        rows[0].price += 5;
+        assert_eq!(history.record(Utc::now(), &rows).unwrap(), 1);
+
+        let snapshots = read_snapshots(&path).unwrap();
+        assert_eq!(snapshots.len(), first + 1);
+        let series = line_movements(&snapshots);
+        let moved = &series[&snapshots.last().unwrap().key()];
+        assert_eq!(moved.len(), 2);
+        assert_eq!(moved[1].price, moved[0].price + 5);
+        let header = fs::read_to_string(&path).unwrap();
+        assert_eq!(header.matches("captured_at").count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
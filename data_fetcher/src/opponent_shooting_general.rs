@@ -1,24 +1,47 @@
 use crate::{
     error::FetchError,
     fetch_data,
-    result_sets::{write_vector_to_csv, ResultSetsFetchedResponse},
-    Endpoint,
+    result_sets::{result_set_row, write_rows_to_csv, ResultSetsFetchedResponse},
+    Endpoint, FetchContext,
 };
 
-pub async fn fetch_opponent_shooting_general() -> Result<(), FetchError> {
+result_set_row! {
+    /// One team from `leaguedashoppptshot`: shots its opponents took.
+    pub struct OpponentShootingRow {
+        "TEAM_ID" => team_id: i64,
+        "TEAM_NAME" => team_name: String,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "GP" => gp: i64,
+        "G" => g: i64,
+        "FGA_FREQUENCY" => fga_frequency: f64,
+        "FGM" => fgm: f64,
+        "FGA" => fga: f64,
+        "FG_PCT" => fg_pct: f64,
+        "EFG_PCT" => efg_pct: f64,
+        "FG2A_FREQUENCY" => fg2a_frequency: f64,
+        "FG2M" => fg2m: f64,
+        "FG2A" => fg2a: f64,
+        "FG2_PCT" => fg2_pct: f64,
+        "FG3A_FREQUENCY" => fg3a_frequency: f64,
+        "FG3M" => fg3m: f64,
+        "FG3A" => fg3a: f64,
+        "FG3_PCT" => fg3_pct: f64,
+    }
+}
+
+pub async fn fetch_opponent_shooting_general(ctx: &FetchContext) -> Result<(), FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::OpponentShootingGeneral.url();
+    let endpoint = Endpoint::OpponentShootingGeneral.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::OpponentShootingGeneral.file_name();
 
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
     save_file_path.set_extension("csv");
 
     // Fetch Data
-    let data: ResultSetsFetchedResponse = fetch_data(endpoint, file_name).await?;
-    let headers = &data.resultSets[0].headers;
-    let rows = &data.resultSets[0].rowSet;
-    write_vector_to_csv(Some(headers.to_vec()), rows.to_vec(), save_file_path).unwrap();
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::OpponentShootingGeneral, &endpoint, file_name).await?;
+    let rows: Vec<OpponentShootingRow> = data.first_result_set()?.rows()?;
+    write_rows_to_csv(&rows, save_file_path)?;
     Ok(())
 }
@@ -0,0 +1,279 @@
+use crate::{
+    box_scores::{box_score_paths, fetch_box_score, BOX_SCORE_ENDPOINTS},
+    error::FetchError,
+    pipeline::join_failure,
+    play_by_play::{fetch_play_by_play, play_by_play_path},
+    Endpoint, FetchContext, OutputDirs,
+};
+use serde_json::Value;
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Arc};
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing::{error, info};
+
+/// Finished games in a prepared schedule (`fetched_SeasonSchedule_data.json`),
+/// in schedule order.
+pub fn completed_game_ids(schedule_path: &Path) -> Result<Vec<String>, FetchError> {
+    let game_dates: Vec<Value> = serde_json::from_slice(&fs::read(schedule_path)?)?;
+    Ok(game_dates
+        .iter()
+        .flat_map(|date| date["games"].as_array().into_iter().flatten())
+        .filter(|game| game["gameStatus"] == 3)
+        .filter_map(|game| game["gameId"].as_str().map(str::to_string))
+        .collect())
+}
+
+/// Whether `endpoint`'s output for `game_id` is complete in `dirs`. For a
+/// box score that is its players file, which is written last.
+fn is_on_disk(dirs: &OutputDirs, endpoint: Endpoint, game_id: &str) -> Result<bool, FetchError> {
+    match endpoint {
+        Endpoint::PlayByPlay => Ok(play_by_play_path(dirs, game_id).exists()),
+        _ => Ok(box_score_paths(dirs, endpoint, game_id)?.0.exists()),
+    }
+}
+
+/// Runs the per-game fetcher for `endpoint` for the game in `ctx.query`.
+async fn fetch_game(endpoint: Endpoint, ctx: &FetchContext) -> Result<(), FetchError> {
+    let result = match endpoint {
+        Endpoint::PlayByPlay => fetch_play_by_play(ctx).await,
+        _ => fetch_box_score(endpoint, ctx).await,
+    };
+    // Per-game runs write no manifest, so don't let their requests pile up.
+    ctx.requests.take(endpoint);
+    result
+}
+
+#[derive(Debug)]
+pub struct GameFailure {
+    pub game_id: String,
+    pub endpoint: Endpoint,
+    pub error: FetchError,
+}
+
+/// What one run of [`fetch_games`] did.
+#[derive(Debug, Default)]
+pub struct GameFetchReport {
+    pub fetched: usize,
+    pub on_disk: usize,
+    pub failures: Vec<GameFailure>,
+}
+
+impl GameFetchReport {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for GameFetchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} fetched, {} already on disk, {} failed",
+            self.fetched,
+            self.on_disk,
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            writeln!(
+                f,
+                "{} {:<20} FAILED: {}",
+                failure.game_id,
+                format!("{:?}", failure.endpoint),
+                failure.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Fetches each of `endpoints` (box scores or play-by-play) for every game
+/// in `game_ids` into `ctx.dirs`, with at most `concurrency` requests in
+/// flight.
+///
+/// Games already on disk are skipped, so running this after every game day
+/// only fetches the new games. A failing game is recorded in the report and
+/// never stops the others. Any other endpoint fails the run before anything
+/// is fetched.
+pub async fn fetch_games(
+    game_ids: &[String],
+    endpoints: &[Endpoint],
+    ctx: &FetchContext,
+    concurrency: usize,
+) -> Result<GameFetchReport, FetchError> {
+    if let Some(&endpoint) = endpoints
+        .iter()
+        .find(|e| **e != Endpoint::PlayByPlay && !BOX_SCORE_ENDPOINTS.contains(e))
+    {
+        return Err(FetchError::NotABoxScore(endpoint));
+    }
+    ctx.dirs.create()?;
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut spawned = HashMap::new();
+    let mut report = GameFetchReport::default();
+
+    for game_id in game_ids {
+        for &endpoint in endpoints {
+            if is_on_disk(&ctx.dirs, endpoint, game_id)? {
+                report.on_disk += 1;
+                continue;
+            }
+            let permits = Arc::clone(&permits);
+            let mut ctx = ctx.clone();
+            ctx.query = ctx.query.with_game_id(game_id.as_str());
+            let order = tasks.len();
+            let task = tasks.spawn(async move {
+                let _permit = permits
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed");
+                fetch_game(endpoint, &ctx).await
+            });
+            spawned.insert(task.id(), (order, game_id.clone(), endpoint));
+        }
+    }
+    info!(
+        "Fetching {} games' data, {} already on disk",
+        tasks.len(),
+        report.on_disk
+    );
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        let (id, result) = match joined {
+            Ok((id, result)) => (id, result),
+            Err(e) => (e.id(), Err(join_failure(e))),
+        };
+        let (order, game_id, endpoint) = spawned.remove(&id).expect("every task is recorded");
+        results.push((order, game_id, endpoint, result));
+    }
+    // Report failures in request order regardless of completion order.
+    results.sort_by_key(|(order, ..)| *order);
+    for (_, game_id, endpoint, result) in results {
+        match result {
+            Ok(()) => report.fetched += 1,
+            Err(e) => {
+                error!("{:?} for game {} failed: {}", endpoint, game_id, e);
+                report.failures.push(GameFailure {
+                    game_id,
+                    endpoint,
+                    error: e,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_server::PanicsOn, query::StatsQuery, transport::FixtureTransport};
+    use std::env;
+
+    #[test]
+    fn completed_games_come_from_the_schedule() {
+        let seed = fs::read("../data/seed_data/scheduleLeagueV2_51.json").unwrap();
+        let json: Value = serde_json::from_slice(&seed).unwrap();
+        let dir = env::temp_dir().join("nba_completed_games");
+        fs::create_dir_all(&dir).unwrap();
+        let schedule = dir.join("fetched_SeasonSchedule_data.json");
+        fs::write(&schedule, json["leagueSchedule"]["gameDates"].to_string()).unwrap();
+
+        let game_ids = completed_game_ids(&schedule).unwrap();
+        assert!(game_ids.contains(&"0022400201".to_string()));
+        assert!(game_ids.iter().all(|id| id.len() == 10));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failures_name_the_game() {
+        let dir = env::temp_dir().join("nba_per_game_failures");
+        let _ = fs::remove_dir_all(&dir);
+        let transport = FixtureTransport::seed_data()
+            .with_file(Endpoint::BoxScoreUsage, dir.join("missing.json"));
+        let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::under(&dir))
+            .with_transport(Arc::new(transport));
+        let game_ids = vec!["0022400201".to_string(), "0022400202".to_string()];
+
+        let report = fetch_games(
+            &game_ids,
+            &[Endpoint::PlayByPlay, Endpoint::BoxScoreUsage],
+            &ctx,
+            3,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.fetched, 2);
+        let failed: Vec<_> = report
+            .failures
+            .iter()
+            .map(|f| (f.game_id.as_str(), f.endpoint))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                ("0022400201", Endpoint::BoxScoreUsage),
+                ("0022400202", Endpoint::BoxScoreUsage)
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn other_endpoints_are_rejected_up_front() {
+        let dir = env::temp_dir().join("nba_per_game_rejected");
+        let _ = fs::remove_dir_all(&dir);
+        let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::under(&dir))
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+        let game_ids = vec!["0022400201".to_string()];
+
+        let error = fetch_games(
+            &game_ids,
+            &[Endpoint::BoxScoreUsage, Endpoint::PlayerIndex],
+            &ctx,
+            1,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            FetchError::NotABoxScore(Endpoint::PlayerIndex)
+        ));
+        assert!(!dir.exists());
+        assert!(box_score_paths(&ctx.dirs, Endpoint::PlayByPlay, "0022400201").is_err());
+    }
+
+    #[tokio::test]
+    async fn a_panicking_fetch_fails_only_its_game() {
+        let dir = env::temp_dir().join("nba_per_game_panic");
+        let _ = fs::remove_dir_all(&dir);
+        let transport = PanicsOn(Endpoint::BoxScoreUsage, FixtureTransport::seed_data());
+        let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::under(&dir))
+            .with_transport(Arc::new(transport));
+        let game_ids = vec!["0022400201".to_string()];
+
+        let report = fetch_games(
+            &game_ids,
+            &[Endpoint::BoxScoreUsage, Endpoint::PlayByPlay],
+            &ctx,
+            2,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.fetched, 1);
+        assert_eq!(report.failures.len(), 1);
+        let failure = &report.failures[0];
+        assert_eq!(failure.game_id, "0022400201");
+        assert_eq!(failure.endpoint, Endpoint::BoxScoreUsage);
+        assert!(matches!(
+            &failure.error,
+            FetchError::Panicked(m) if m == "no transport for BoxScoreUsage"
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    cache::CacheStatus, error::FetchError, fetch_endpoint, manifest::FetchedRequest,
+    schema::SchemaDrift, Endpoint, FetchContext,
+};
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::Semaphore,
+    task::{JoinError, JoinSet},
+};
+use tracing::{error, info};
+
+/// Fetches run at once when nothing else is configured.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Debug)]
+pub enum FetchOutcome {
+    Success,
+    Skipped(String),
+    Failed(FetchError),
+}
+
+#[derive(Debug)]
+pub struct EndpointReport {
+    pub endpoint: Endpoint,
+    pub outcome: FetchOutcome,
+    pub started_at: DateTime<Utc>,
+    pub elapsed: Duration,
+    /// How the response differed from its recorded schema, if it did.
+    pub drift: Option<SchemaDrift>,
+    /// Whether the response came from the HTTP cache.
+    pub cache: Option<CacheStatus>,
+    /// The requests the fetch made, in order.
+    pub requests: Vec<FetchedRequest>,
+}
+
+/// What happened to each endpoint in one run.
+#[derive(Debug, Default)]
+pub struct FetchReport {
+    pub endpoints: Vec<EndpointReport>,
+}
+
+impl FetchReport {
+    pub fn push(&mut self, endpoint: Endpoint, outcome: FetchOutcome, elapsed: Duration) {
+        self.endpoints.push(EndpointReport {
+            endpoint,
+            outcome,
+            started_at: Utc::now(),
+            elapsed,
+            drift: None,
+            cache: None,
+            requests: Vec::new(),
+        });
+    }
+
+    pub fn cache_hits(&self) -> usize {
+        self.endpoints
+            .iter()
+            .filter(|r| r.cache.is_some_and(|c| c.is_hit()))
+            .count()
+    }
+
+    pub fn drifted(&self) -> impl Iterator<Item = &EndpointReport> {
+        self.endpoints.iter().filter(|r| r.drift.is_some())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &EndpointReport> {
+        self.endpoints
+            .iter()
+            .filter(|r| matches!(r.outcome, FetchOutcome::Failed(_)))
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    pub fn extend(&mut self, other: FetchReport) {
+        self.endpoints.extend(other.endpoints);
+    }
+}
+
+impl fmt::Display for FetchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in &self.endpoints {
+            let outcome = match &report.outcome {
+                FetchOutcome::Success => match report.cache {
+                    Some(cache) => format!("ok ({})", cache),
+                    None => "ok".to_string(),
+                },
+                FetchOutcome::Skipped(reason) => format!("skipped ({})", reason),
+                FetchOutcome::Failed(e) => format!("FAILED: {}", e),
+            };
+            writeln!(
+                f,
+                "{:<30} {:>8.2?}  {}",
+                format!("{:?}", report.endpoint),
+                report.elapsed,
+                outcome
+            )?;
+            if let Some(drift) = &report.drift {
+                writeln!(f, "{:<30} schema drift: {}", "", drift)?;
+            }
+        }
+        let cached = self.endpoints.iter().filter(|r| r.cache.is_some()).count();
+        if cached > 0 {
+            writeln!(
+                f,
+                "{} of {} served from the HTTP cache",
+                self.cache_hits(),
+                cached
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Fetches `endpoints` with at most `concurrency` requests in flight.
+/// A failing endpoint is recorded in the report and never stops the others,
+/// and neither does one whose fetcher panics.
+pub async fn fetch_concurrently(
+    endpoints: &[Endpoint],
+    ctx: &FetchContext,
+    concurrency: usize,
+) -> FetchReport {
+    let permits = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut spawned = HashMap::new();
+
+    for &endpoint in endpoints {
+        let permits = Arc::clone(&permits);
+        let ctx = ctx.clone();
+        let task = tasks.spawn(async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            let started_at = Utc::now();
+            let started = Instant::now();
+            let outcome = fetch_endpoint(endpoint, &ctx).await;
+            match &outcome {
+                FetchOutcome::Failed(e) => error!("{:?} failed: {}", endpoint, e),
+                _ => info!("{:?} finished in {:?}", endpoint, started.elapsed()),
+            }
+            EndpointReport {
+                endpoint,
+                outcome,
+                started_at,
+                elapsed: started.elapsed(),
+                drift: ctx.schemas.take_drift(endpoint),
+                cache: ctx.transport.take_cache_status(endpoint),
+                requests: ctx.requests.take(endpoint),
+            }
+        });
+        spawned.insert(task.id(), (endpoint, Utc::now(), Instant::now()));
+    }
+
+    let mut report = FetchReport::default();
+    while let Some(joined) = tasks.join_next_with_id().await {
+        match joined {
+            Ok((_, endpoint_report)) => report.endpoints.push(endpoint_report),
+            Err(e) => {
+                let (endpoint, started_at, started) = spawned[&e.id()];
+                let error = join_failure(e);
+                error!("{:?} failed: {}", endpoint, error);
+                report.endpoints.push(EndpointReport {
+                    endpoint,
+                    outcome: FetchOutcome::Failed(error),
+                    started_at,
+                    elapsed: started.elapsed(),
+                    drift: ctx.schemas.take_drift(endpoint),
+                    cache: ctx.transport.take_cache_status(endpoint),
+                    requests: ctx.requests.take(endpoint),
+                });
+            }
+        }
+    }
+
+    // Keep the report in request order regardless of completion order.
+    report
+        .endpoints
+        .sort_by_key(|r| endpoints.iter().position(|e| *e == r.endpoint));
+    report
+}
+
+/// A task that didn't finish as a failure of whatever it was fetching,
+/// with the message it panicked with if it was given one.
+pub(crate) fn join_failure(e: JoinError) -> FetchError {
+    if !e.is_panic() {
+        return FetchError::Panicked(e.to_string());
+    }
+    let payload = e.into_panic();
+    FetchError::Panicked(match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "unknown panic".to_string(), |m| m.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_server::PanicsOn, query::StatsQuery, transport::FixtureTransport, OutputDirs,
+    };
+    use std::{env, fs};
+
+    #[tokio::test]
+    async fn a_panicking_fetcher_fails_only_its_endpoint() {
+        let dir = env::temp_dir().join("nba_pipeline_panic");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let transport = PanicsOn(Endpoint::PlayerIndex, FixtureTransport::seed_data());
+        let ctx =
+            FetchContext::new(StatsQuery::default(), dirs).with_transport(Arc::new(transport));
+
+        let endpoints = [
+            Endpoint::TeamsGeneralAdvanced,
+            Endpoint::PlayerIndex,
+            Endpoint::Standings,
+        ];
+        let report = fetch_concurrently(&endpoints, &ctx, 2).await;
+
+        let order: Vec<_> = report.endpoints.iter().map(|r| r.endpoint).collect();
+        assert_eq!(order, endpoints);
+        let failed: Vec<_> = report.failures().map(|r| r.endpoint).collect();
+        assert_eq!(failed, vec![Endpoint::PlayerIndex]);
+        assert!(matches!(
+            &report.endpoints[1].outcome,
+            FetchOutcome::Failed(FetchError::Panicked(m)) if m == "no transport for PlayerIndex"
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn report_lists_failures() {
+        let mut report = FetchReport::default();
+        report.push(
+            Endpoint::PlayerIndex,
+            FetchOutcome::Success,
+            Duration::from_millis(120),
+        );
+        report.push(
+            Endpoint::InjuryReport,
+            FetchOutcome::Failed(FetchError::Timeout("https://example.com".into())),
+            Duration::from_secs(30),
+        );
+        report.push(
+            Endpoint::SeasonSchedule,
+            FetchOutcome::Skipped("already on disk".into()),
+            Duration::ZERO,
+        );
+
+        assert!(!report.is_success());
+        let failed: Vec<_> = report.failures().map(|r| r.endpoint).collect();
+        assert_eq!(failed, vec![Endpoint::InjuryReport]);
+
+        let printed = report.to_string();
+        assert!(printed.contains("FAILED: Request timed out"));
+        assert!(printed.contains("skipped (already on disk)"));
+    }
+}
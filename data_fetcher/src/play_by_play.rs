@@ -0,0 +1,293 @@
+use crate::{error::FetchError, fetch_data, Endpoint, FetchContext, OutputDirs};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Deserialize, Debug)]
+#[allow(unused)]
+struct PlayByPlayFetchedResponse {
+    meta: Value,
+    game: PlayByPlayGame,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PlayByPlayGame {
+    game_id: String,
+    actions: Vec<PlayByPlayAction>,
+}
+
+/// One event in a game's play-by-play.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayByPlayAction {
+    /// Increases through the game but skips numbers; order by this.
+    pub action_number: u32,
+    pub period: u32,
+    /// ISO 8601 duration left in the period, e.g. `PT11M41.00S`.
+    pub clock: String,
+    /// e.g. `2pt`, `3pt`, `freethrow`, `rebound`, `substitution`, `period`.
+    pub action_type: String,
+    #[serde(default)]
+    pub sub_type: String,
+    /// Missing for actions that belong to neither team, like a period
+    /// starting.
+    #[serde(default)]
+    pub team_id: Option<i64>,
+    #[serde(default)]
+    pub team_tricode: String,
+    /// `0` when no player is involved.
+    #[serde(default)]
+    pub person_id: i64,
+    #[serde(default, rename = "playerNameI")]
+    pub player_name: String,
+    /// The score once this action is done.
+    #[serde(deserialize_with = "score")]
+    pub score_home: u32,
+    #[serde(deserialize_with = "score")]
+    pub score_away: u32,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// The live feed sends scores as strings (`"12"`); accept numbers too.
+fn score<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Number(n) => n
+            .as_u64()
+            .map(|n| n as u32)
+            .ok_or_else(|| de::Error::custom(format!("invalid score {}", n))),
+        Value::String(s) => s.parse().map_err(de::Error::custom),
+        other => Err(de::Error::custom(format!("invalid score {}", other))),
+    }
+}
+
+impl PlayByPlayAction {
+    /// Time left in the period, in hundredths of a second.
+    pub fn clock_hundredths(&self) -> Option<u32> {
+        parse_clock(&self.clock)
+    }
+}
+
+/// `PT11M41.50S` as hundredths of a second.
+fn parse_clock(clock: &str) -> Option<u32> {
+    let (minutes, seconds) = clock
+        .strip_prefix("PT")?
+        .strip_suffix('S')?
+        .split_once('M')?;
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    let fraction = format!("{:0<2}", fraction);
+    let hundredths: u32 = fraction.get(..2)?.parse().ok()?;
+    Some((minutes.parse::<u32>().ok()? * 60 + whole.parse::<u32>().ok()?) * 100 + hundredths)
+}
+
+fn format_clock(hundredths: u32) -> String {
+    format!(
+        "PT{:02}M{:02}.{:02}S",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
+
+/// A column of strings stored once each, with every row pointing at one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Dictionary {
+    pub values: Vec<String>,
+    pub codes: Vec<u32>,
+}
+
+impl Dictionary {
+    fn encode<'a>(column: impl Iterator<Item = &'a str>) -> Self {
+        let mut dictionary = Dictionary::default();
+        let mut index: HashMap<&str, u32> = HashMap::new();
+        for value in column {
+            let code = *index.entry(value).or_insert_with(|| {
+                dictionary.values.push(value.to_string());
+                dictionary.values.len() as u32 - 1
+            });
+            dictionary.codes.push(code);
+        }
+        dictionary
+    }
+
+    fn get(&self, row: usize) -> Option<&str> {
+        let code = *self.codes.get(row)?;
+        self.values.get(code as usize).map(String::as_str)
+    }
+}
+
+/// One game's play-by-play stored column by column. Repeated strings are
+/// dictionary-encoded and the clock is kept as hundredths of a second, so a
+/// gzipped game is a small fraction of the raw feed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayByPlayColumns {
+    pub game_id: String,
+    pub action_number: Vec<u32>,
+    pub period: Vec<u32>,
+    pub clock: Vec<u32>,
+    pub action_type: Dictionary,
+    pub sub_type: Dictionary,
+    pub team_id: Vec<Option<i64>>,
+    pub team_tricode: Dictionary,
+    pub person_id: Vec<i64>,
+    pub player_name: Dictionary,
+    pub score_home: Vec<u32>,
+    pub score_away: Vec<u32>,
+    pub description: Vec<String>,
+}
+
+impl PlayByPlayColumns {
+    /// Fails if an action's clock isn't an ISO 8601 duration.
+    pub fn from_actions(game_id: &str, actions: &[PlayByPlayAction]) -> Result<Self, FetchError> {
+        let clock = actions
+            .iter()
+            .map(|a| {
+                a.clock_hundredths()
+                    .ok_or_else(|| FetchError::UnexpectedShape {
+                        endpoint: Endpoint::PlayByPlay,
+                        pointer: format!("/game/actions/{}/clock", a.action_number),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(PlayByPlayColumns {
+            game_id: game_id.to_string(),
+            action_number: actions.iter().map(|a| a.action_number).collect(),
+            period: actions.iter().map(|a| a.period).collect(),
+            clock,
+            action_type: Dictionary::encode(actions.iter().map(|a| a.action_type.as_str())),
+            sub_type: Dictionary::encode(actions.iter().map(|a| a.sub_type.as_str())),
+            team_id: actions.iter().map(|a| a.team_id).collect(),
+            team_tricode: Dictionary::encode(actions.iter().map(|a| a.team_tricode.as_str())),
+            person_id: actions.iter().map(|a| a.person_id).collect(),
+            player_name: Dictionary::encode(actions.iter().map(|a| a.player_name.as_str())),
+            score_home: actions.iter().map(|a| a.score_home).collect(),
+            score_away: actions.iter().map(|a| a.score_away).collect(),
+            description: actions.iter().map(|a| a.description.clone()).collect(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.action_number.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.action_number.is_empty()
+    }
+
+    /// The actions back as rows. Returns `None` if the columns have
+    /// different lengths.
+    pub fn actions(&self) -> Option<Vec<PlayByPlayAction>> {
+        (0..self.len())
+            .map(|row| {
+                Some(PlayByPlayAction {
+                    action_number: self.action_number[row],
+                    period: *self.period.get(row)?,
+                    clock: format_clock(*self.clock.get(row)?),
+                    action_type: self.action_type.get(row)?.to_string(),
+                    sub_type: self.sub_type.get(row)?.to_string(),
+                    team_id: *self.team_id.get(row)?,
+                    team_tricode: self.team_tricode.get(row)?.to_string(),
+                    person_id: *self.person_id.get(row)?,
+                    player_name: self.player_name.get(row)?.to_string(),
+                    score_home: *self.score_home.get(row)?,
+                    score_away: *self.score_away.get(row)?,
+                    description: self.description.get(row)?.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the columns as gzipped JSON.
+    pub fn write(&self, path: &Path) -> Result<(), FetchError> {
+        let file = File::create(path)?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.finish()?.flush()?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, FetchError> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(GzDecoder::new(BufReader::new(
+            file,
+        )))?)
+    }
+}
+
+/// Where one game's columns are written, e.g. `0022400201.pbp.json.gz`.
+pub fn play_by_play_path(dirs: &OutputDirs, game_id: &str) -> PathBuf {
+    dirs.prepared.join(format!("{}.pbp.json.gz", game_id))
+}
+
+/// Fetches the play-by-play for the game in `ctx.query` and writes it as
+/// [`PlayByPlayColumns`].
+pub async fn fetch_play_by_play(ctx: &FetchContext) -> Result<(), FetchError> {
+    let game_id = ctx
+        .query
+        .game_id()
+        .ok_or(FetchError::MissingGameId(Endpoint::PlayByPlay))?;
+    let url = Endpoint::PlayByPlay.url(&ctx.query);
+    let file_name = format!("{}_playbyplay", game_id);
+
+    let data: PlayByPlayFetchedResponse =
+        fetch_data(ctx, Endpoint::PlayByPlay, &url, &file_name).await?;
+
+    PlayByPlayColumns::from_actions(&data.game.game_id, &data.game.actions)?
+        .write(&play_by_play_path(&ctx.dirs, game_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport};
+    use std::{env, fs, sync::Arc};
+
+    #[test]
+    fn clocks_round_trip_through_hundredths() {
+        assert_eq!(parse_clock("PT12M00.00S"), Some(72_000));
+        assert_eq!(parse_clock("PT10M47.50S"), Some(64_750));
+        assert_eq!(parse_clock("PT00M01.2S"), Some(120));
+        assert_eq!(parse_clock("11:41"), None);
+        assert_eq!(format_clock(64_750), "PT10M47.50S");
+    }
+
+    #[tokio::test]
+    async fn play_by_play_is_stored_as_columns() {
+        let dir = env::temp_dir().join("nba_play_by_play");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::new().with_game_id("0022400201"), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_play_by_play(&ctx).await.unwrap();
+
+        let path = play_by_play_path(&dirs, "0022400201");
+        let columns = PlayByPlayColumns::read(&path).unwrap();
+        assert_eq!(columns.len(), 16);
+        assert!(columns.action_type.values.len() < columns.len());
+
+        let seed = fs::read("../data/seed_data/playbyplay.json").unwrap();
+        let json: PlayByPlayFetchedResponse = serde_json::from_slice(&seed).unwrap();
+        let actions = columns.actions().unwrap();
+        assert_eq!(actions, json.game.actions);
+        assert!(fs::metadata(&path).unwrap().len() < seed.len() as u64 / 3);
+
+        let dunk = &actions[2];
+        assert_eq!(dunk.action_type, "2pt");
+        assert_eq!(dunk.team_tricode, "DET");
+        assert_eq!(dunk.player_name, "J. Duren");
+        assert_eq!((dunk.score_home, dunk.score_away), (2, 0));
+        assert_eq!(actions[0].team_id, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,24 +1,99 @@
 use crate::{
     error::FetchError,
     fetch_data,
-    result_sets::{write_vector_to_csv, ResultSetsFetchedResponse},
-    Endpoint,
+    result_sets::{result_set_row, write_result_set, ResultSetsFetchedResponse},
+    Endpoint, FetchContext,
 };
 
-pub async fn fetch_player_general_averages() -> Result<(), FetchError> {
+result_set_row! {
+    /// One player from `leaguedashplayerstats`.
+    pub struct PlayerGeneralAverageRow {
+        "PLAYER_ID" => player_id: i64,
+        "PLAYER_NAME" => player_name: String,
+        "NICKNAME" => nickname: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "AGE" => age: f64,
+        "GP" => gp: i64,
+        "W" => w: i64,
+        "L" => l: i64,
+        "W_PCT" => w_pct: f64,
+        "MIN" => min: f64,
+        "FGM" => fgm: f64,
+        "FGA" => fga: f64,
+        "FG_PCT" => fg_pct: f64,
+        "FG3M" => fg3m: f64,
+        "FG3A" => fg3a: f64,
+        "FG3_PCT" => fg3_pct: f64,
+        "FTM" => ftm: f64,
+        "FTA" => fta: f64,
+        "FT_PCT" => ft_pct: f64,
+        "OREB" => oreb: f64,
+        "DREB" => dreb: f64,
+        "REB" => reb: f64,
+        "AST" => ast: f64,
+        "TOV" => tov: f64,
+        "STL" => stl: f64,
+        "BLK" => blk: f64,
+        "BLKA" => blka: f64,
+        "PF" => pf: f64,
+        "PFD" => pfd: f64,
+        "PTS" => pts: f64,
+        "PLUS_MINUS" => plus_minus: f64,
+        "NBA_FANTASY_PTS" => nba_fantasy_pts: f64,
+        "DD2" => dd2: i64,
+        "TD3" => td3: i64,
+        "WNBA_FANTASY_PTS" => wnba_fantasy_pts: f64,
+        "GP_RANK" => gp_rank: i64,
+        "W_RANK" => w_rank: i64,
+        "L_RANK" => l_rank: i64,
+        "W_PCT_RANK" => w_pct_rank: i64,
+        "MIN_RANK" => min_rank: i64,
+        "FGM_RANK" => fgm_rank: i64,
+        "FGA_RANK" => fga_rank: i64,
+        "FG_PCT_RANK" => fg_pct_rank: i64,
+        "FG3M_RANK" => fg3m_rank: i64,
+        "FG3A_RANK" => fg3a_rank: i64,
+        "FG3_PCT_RANK" => fg3_pct_rank: i64,
+        "FTM_RANK" => ftm_rank: i64,
+        "FTA_RANK" => fta_rank: i64,
+        "FT_PCT_RANK" => ft_pct_rank: i64,
+        "OREB_RANK" => oreb_rank: i64,
+        "DREB_RANK" => dreb_rank: i64,
+        "REB_RANK" => reb_rank: i64,
+        "AST_RANK" => ast_rank: i64,
+        "TOV_RANK" => tov_rank: i64,
+        "STL_RANK" => stl_rank: i64,
+        "BLK_RANK" => blk_rank: i64,
+        "BLKA_RANK" => blka_rank: i64,
+        "PF_RANK" => pf_rank: i64,
+        "PFD_RANK" => pfd_rank: i64,
+        "PTS_RANK" => pts_rank: i64,
+        "PLUS_MINUS_RANK" => plus_minus_rank: i64,
+        "NBA_FANTASY_PTS_RANK" => nba_fantasy_pts_rank: i64,
+        "DD2_RANK" => dd2_rank: i64,
+        "TD3_RANK" => td3_rank: i64,
+        "WNBA_FANTASY_PTS_RANK" => wnba_fantasy_pts_rank: i64,
+    }
+}
+
+pub async fn fetch_player_general_averages(ctx: &FetchContext) -> Result<(), FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::PlayerGeneralAverages.url();
+    let endpoint = Endpoint::PlayerGeneralAverages.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::PlayerGeneralAverages.file_name();
 
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
     save_file_path.set_extension("csv");
 
     // Fetch Data
-    let data: ResultSetsFetchedResponse = fetch_data(endpoint, file_name).await?;
-    let headers = &data.resultSets[0].headers;
-    let rows = &data.resultSets[0].rowSet;
-    write_vector_to_csv(Some(headers.to_vec()), rows.to_vec(), save_file_path).unwrap();
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::PlayerGeneralAverages, &endpoint, file_name).await?;
+    write_result_set::<PlayerGeneralAverageRow>(
+        data.first_result_set()?,
+        Endpoint::PlayerGeneralAverages,
+        &ctx.query,
+        save_file_path,
+    )?;
     Ok(())
 }
@@ -1,23 +1,54 @@
 use crate::{
     error::FetchError,
     fetch_data,
-    result_sets::{write_vector_to_csv, ResultSetsFetchedResponse},
-    Endpoint,
+    result_sets::{result_set_row, write_rows_to_csv, ResultSetsFetchedResponse},
+    Endpoint, FetchContext,
 };
 
-pub async fn fetch_player_index() -> Result<(), FetchError> {
+result_set_row! {
+    /// One player from `playerindex`.
+    pub struct PlayerIndexRow {
+        "PERSON_ID" => person_id: i64,
+        "PLAYER_LAST_NAME" => player_last_name: String,
+        "PLAYER_FIRST_NAME" => player_first_name: String,
+        "PLAYER_SLUG" => player_slug: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_SLUG" => team_slug: Option<String>,
+        "IS_DEFUNCT" => is_defunct: i64,
+        "TEAM_CITY" => team_city: Option<String>,
+        "TEAM_NAME" => team_name: Option<String>,
+        "TEAM_ABBREVIATION" => team_abbreviation: Option<String>,
+        "JERSEY_NUMBER" => jersey_number: Option<String>,
+        "POSITION" => position: Option<String>,
+        "HEIGHT" => height: Option<String>,
+        "WEIGHT" => weight: Option<String>,
+        "COLLEGE" => college: Option<String>,
+        "COUNTRY" => country: Option<String>,
+        "DRAFT_YEAR" => draft_year: Option<i64>,
+        "DRAFT_ROUND" => draft_round: Option<i64>,
+        "DRAFT_NUMBER" => draft_number: Option<i64>,
+        "ROSTER_STATUS" => roster_status: Option<f64>,
+        "FROM_YEAR" => from_year: Option<String>,
+        "TO_YEAR" => to_year: Option<String>,
+        "PTS" => pts: Option<f64>,
+        "REB" => reb: Option<f64>,
+        "AST" => ast: Option<f64>,
+        "STATS_TIMEFRAME" => stats_timeframe: Option<String>,
+    }
+}
+
+pub async fn fetch_player_index(ctx: &FetchContext) -> Result<(), FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::PlayerIndex.url();
+    let endpoint = Endpoint::PlayerIndex.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::PlayerIndex.file_name();
 
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
     save_file_path.set_extension("csv");
     // Fetch Data
-    let data: ResultSetsFetchedResponse = fetch_data(endpoint, file_name).await?;
-    let headers = &data.resultSets[0].headers;
-    let rows = &data.resultSets[0].rowSet;
-    write_vector_to_csv(Some(headers.to_vec()), rows.to_vec(), save_file_path).unwrap();
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::PlayerIndex, &endpoint, file_name).await?;
+    let rows: Vec<PlayerIndexRow> = data.first_result_set()?.rows()?;
+    write_rows_to_csv(&rows, save_file_path)?;
     Ok(())
 }
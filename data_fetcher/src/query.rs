@@ -0,0 +1,463 @@
+use chrono::{Datelike, Local, NaiveDate};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// An NBA season identified by the calendar year it starts in,
+/// rendered the way stats.nba.com expects it (e.g. `2024-25`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Season(u16);
+
+impl Season {
+    pub fn new(start_year: u16) -> Self {
+        Season(start_year)
+    }
+
+    /// The season in progress today; a new season starts in October.
+    pub fn current() -> Self {
+        let today = Local::now().date_naive();
+        if today.month() >= 10 {
+            Season(today.year() as u16)
+        } else {
+            Season(today.year() as u16 - 1)
+        }
+    }
+
+    pub fn start_year(&self) -> u16 {
+        self.0
+    }
+
+    pub fn next(&self) -> Self {
+        Season(self.0 + 1)
+    }
+}
+
+impl Default for Season {
+    fn default() -> Self {
+        Season::current()
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:02}", self.0, (self.0 + 1) % 100)
+    }
+}
+
+impl FromStr for Season {
+    type Err = String;
+
+    /// Accepts either `2024-25` or a bare start year like `2024`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let start = s.split('-').next().unwrap_or_default();
+        start
+            .parse::<u16>()
+            .map(Season)
+            .map_err(|_| format!("invalid season: {}", s))
+    }
+}
+
+/// Every franchise's stats.nba.com `TEAM_ID`.
+pub const TEAM_IDS: RangeInclusive<u32> = 1610612737..=1610612766;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeasonType {
+    PreSeason,
+    #[default]
+    RegularSeason,
+    Playoffs,
+    PlayIn,
+    IST,
+}
+
+impl SeasonType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeasonType::PreSeason => "Pre Season",
+            SeasonType::RegularSeason => "Regular Season",
+            SeasonType::Playoffs => "Playoffs",
+            SeasonType::PlayIn => "PlayIn",
+            SeasonType::IST => "IST",
+        }
+    }
+
+    /// Directory-safe name used for on-disk partitions.
+    pub fn slug(&self) -> &'static str {
+        match self {
+            SeasonType::PreSeason => "pre_season",
+            SeasonType::RegularSeason => "regular_season",
+            SeasonType::Playoffs => "playoffs",
+            SeasonType::PlayIn => "play_in",
+            SeasonType::IST => "ist",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PerMode {
+    Totals,
+    #[default]
+    PerGame,
+    Per36,
+    Per48,
+    Per100Possessions,
+}
+
+impl PerMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PerMode::Totals => "Totals",
+            PerMode::PerGame => "PerGame",
+            PerMode::Per36 => "Per36",
+            PerMode::Per48 => "Per48",
+            PerMode::Per100Possessions => "Per100Possessions",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasureType {
+    Base,
+    Advanced,
+    Misc,
+    FourFactors,
+    Scoring,
+    Opponent,
+    Usage,
+    Defense,
+}
+
+impl MeasureType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MeasureType::Base => "Base",
+            MeasureType::Advanced => "Advanced",
+            MeasureType::Misc => "Misc",
+            MeasureType::FourFactors => "Four Factors",
+            MeasureType::Scoring => "Scoring",
+            MeasureType::Opponent => "Opponent",
+            MeasureType::Usage => "Usage",
+            MeasureType::Defense => "Defense",
+        }
+    }
+}
+
+/// The player tracking measures `leaguedashptstats` reports, one per
+/// request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PtMeasureType {
+    SpeedDistance,
+    /// Touches and time of possession.
+    Possessions,
+    Drives,
+    CatchShoot,
+    PullUpShot,
+    /// Rebounds against rebounding chances, contested or not.
+    Rebounding,
+    /// Includes shots defended at the rim.
+    Defense,
+}
+
+impl PtMeasureType {
+    pub const ALL: [PtMeasureType; 7] = [
+        PtMeasureType::SpeedDistance,
+        PtMeasureType::Possessions,
+        PtMeasureType::Drives,
+        PtMeasureType::CatchShoot,
+        PtMeasureType::PullUpShot,
+        PtMeasureType::Rebounding,
+        PtMeasureType::Defense,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PtMeasureType::SpeedDistance => "SpeedDistance",
+            PtMeasureType::Possessions => "Possessions",
+            PtMeasureType::Drives => "Drives",
+            PtMeasureType::CatchShoot => "CatchShoot",
+            PtMeasureType::PullUpShot => "PullUpShot",
+            PtMeasureType::Rebounding => "Rebounding",
+            PtMeasureType::Defense => "Defense",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Home,
+    Road,
+}
+
+impl Location {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Location::Home => "Home",
+            Location::Road => "Road",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Win => "W",
+            Outcome::Loss => "L",
+        }
+    }
+}
+
+/// Query parameters shared by the stats.nba.com endpoints.
+///
+/// The default is the current regular season, per-game, over every game
+/// played, which is what the pipeline pulled before it was parameterized.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StatsQuery {
+    season: Season,
+    season_type: SeasonType,
+    per_mode: PerMode,
+    measure_type: Option<MeasureType>,
+    last_n_games: u32,
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+    location: Option<Location>,
+    outcome: Option<Outcome>,
+    opponent_team_id: u32,
+    team_id: u32,
+    game_id: Option<String>,
+}
+
+impl StatsQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_season(mut self, season: Season) -> Self {
+        self.season = season;
+        self
+    }
+
+    pub fn with_season_type(mut self, season_type: SeasonType) -> Self {
+        self.season_type = season_type;
+        self
+    }
+
+    pub fn with_per_mode(mut self, per_mode: PerMode) -> Self {
+        self.per_mode = per_mode;
+        self
+    }
+
+    /// Overrides the measure type an endpoint would otherwise use
+    /// (`Advanced` for `TeamsGeneralAdvanced`, `Base` elsewhere).
+    pub fn with_measure_type(mut self, measure_type: MeasureType) -> Self {
+        self.measure_type = Some(measure_type);
+        self
+    }
+
+    pub fn with_last_n_games(mut self, last_n_games: u32) -> Self {
+        self.last_n_games = last_n_games;
+        self
+    }
+
+    pub fn with_date_range(mut self, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        self.date_from = from;
+        self.date_to = to;
+        self
+    }
+
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    pub fn with_outcome(mut self, outcome: Outcome) -> Self {
+        self.outcome = Some(outcome);
+        self
+    }
+
+    pub fn with_opponent_team_id(mut self, opponent_team_id: u32) -> Self {
+        self.opponent_team_id = opponent_team_id;
+        self
+    }
+
+    /// The team the per-team endpoints (on/off splits) ask about. Without
+    /// one they go through every team in [`TEAM_IDS`].
+    pub fn with_team_id(mut self, team_id: u32) -> Self {
+        self.team_id = team_id;
+        self
+    }
+
+    /// The game the per-game endpoints (box scores) ask about, e.g.
+    /// `0022400201`.
+    pub fn with_game_id(mut self, game_id: impl Into<String>) -> Self {
+        self.game_id = Some(game_id.into());
+        self
+    }
+
+    pub fn season(&self) -> Season {
+        self.season
+    }
+
+    pub fn season_type(&self) -> SeasonType {
+        self.season_type
+    }
+
+    pub fn per_mode(&self) -> PerMode {
+        self.per_mode
+    }
+
+    pub fn team_id(&self) -> Option<u32> {
+        (self.team_id != 0).then_some(self.team_id)
+    }
+
+    pub fn game_id(&self) -> Option<&str> {
+        self.game_id.as_deref()
+    }
+
+    pub fn measure_type(&self) -> Option<MeasureType> {
+        self.measure_type
+    }
+
+    pub(crate) fn measure_type_or(&self, default: MeasureType) -> &'static str {
+        self.measure_type.unwrap_or(default).as_str()
+    }
+
+    pub(crate) fn last_n_games(&self) -> String {
+        self.last_n_games.to_string()
+    }
+
+    pub(crate) fn date_from(&self) -> String {
+        format_date(self.date_from)
+    }
+
+    pub(crate) fn date_to(&self) -> String {
+        format_date(self.date_to)
+    }
+
+    pub(crate) fn location(&self) -> &'static str {
+        self.location.map_or("", |l| l.as_str())
+    }
+
+    pub(crate) fn outcome(&self) -> &'static str {
+        self.outcome.map_or("", |o| o.as_str())
+    }
+
+    pub(crate) fn opponent_team_id(&self) -> String {
+        self.opponent_team_id.to_string()
+    }
+
+    /// The same query from the other side: the team it names becomes the
+    /// opponent, and its opponent, if any, the team.
+    pub(crate) fn with_teams_swapped(&self) -> Self {
+        Self {
+            team_id: self.opponent_team_id,
+            opponent_team_id: self.team_id,
+            ..self.clone()
+        }
+    }
+}
+
+fn format_date(date: Option<NaiveDate>) -> String {
+    date.map_or(String::new(), |d| d.format("%m/%d/%Y").to_string())
+}
+
+/// Joins `base` and `params` into a URL, form-encoding every value.
+pub(crate) fn build_url(base: &str, params: &[(&str, &str)]) -> String {
+    let mut url = Url::parse(base).expect("base is a valid URL");
+    url.query_pairs_mut().extend_pairs(params);
+    // Form encoding writes spaces as `+`, but stats.nba.com's own pages send
+    // `%20`. A literal `+` has been encoded as `%2B` by now.
+    url.as_str().replace('+', "%20")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn season_formats_like_stats_nba() {
+        assert_eq!(Season::new(2024).to_string(), "2024-25");
+        assert_eq!(Season::new(1999).to_string(), "1999-00");
+        assert_eq!("2019-20".parse::<Season>().unwrap(), Season::new(2019));
+        assert_eq!("2021".parse::<Season>().unwrap(), Season::new(2021));
+        assert!("abcd".parse::<Season>().is_err());
+    }
+
+    #[test]
+    fn build_url_encodes_values() {
+        let query = StatsQuery::new()
+            .with_season_type(SeasonType::Playoffs)
+            .with_date_range(NaiveDate::from_ymd_opt(2025, 1, 15), None);
+        let url = build_url(
+            "https://stats.nba.com/stats/x",
+            &[
+                ("SeasonType", query.season_type().as_str()),
+                ("DateFrom", &query.date_from()),
+                ("DateTo", &query.date_to()),
+            ],
+        );
+        assert_eq!(
+            url,
+            "https://stats.nba.com/stats/x?SeasonType=Playoffs&DateFrom=01%2F15%2F2025&DateTo="
+        );
+
+        let url = build_url(
+            "https://stats.nba.com/stats/x",
+            &[("ISTRound", "East Group A & B"), ("Odd", "1+1=2#50%")],
+        );
+        assert_eq!(
+            url,
+            "https://stats.nba.com/stats/x?ISTRound=East%20Group%20A%20%26%20B&Odd=1%2B1%3D2%2350%25"
+        );
+        let parsed = Url::parse(&url).unwrap();
+        let pairs: Vec<(String, String)> = parsed.query_pairs().into_owned().collect();
+        assert_eq!(pairs[0].1, "East Group A & B");
+        assert_eq!(pairs[1].1, "1+1=2#50%");
+    }
+
+    #[test]
+    fn default_query_reproduces_original_urls() {
+        let query = StatsQuery::new().with_season(Season::new(2024));
+        assert_eq!(
+            crate::Endpoint::TeamsGeneralAdvanced.url(&query),
+            "https://stats.nba.com/stats/leaguedashteamstats?Conference=&DateFrom=&DateTo=&Division=&GameScope=&GameSegment=&Height=&ISTRound=&LastNGames=0&LeagueID=00&Location=&MeasureType=Advanced&Month=0&OpponentTeamID=0&Outcome=&PORound=0&PaceAdjust=N&PerMode=PerGame&Period=0&PlayerExperience=&PlayerPosition=&PlusMinus=N&Rank=N&Season=2024-25&SeasonSegment=&SeasonType=Regular%20Season&ShotClockRange=&StarterBench=&TeamID=0&TwoWay=0&VsConference=&VsDivision="
+        );
+        assert_eq!(
+            crate::Endpoint::PlayerIndex.url(&query),
+            "https://stats.nba.com/stats/playerindex?College=&Country=&DraftPick=&DraftRound=&DraftYear=&Height=&Historical=0&LeagueID=00&Season=2024-25&SeasonType=Regular%20Season&TeamID=0&Weight="
+        );
+    }
+
+    #[test]
+    fn query_overrides_reach_the_url() {
+        let query = StatsQuery::new()
+            .with_season(Season::new(2023))
+            .with_season_type(SeasonType::Playoffs)
+            .with_per_mode(PerMode::Totals)
+            .with_measure_type(MeasureType::Usage)
+            .with_last_n_games(10)
+            .with_location(Location::Home)
+            .with_outcome(Outcome::Win)
+            .with_opponent_team_id(1610612738);
+        let url = crate::Endpoint::PlayerGeneralAverages.url(&query);
+        for expected in [
+            "Season=2023-24",
+            "SeasonType=Playoffs",
+            "PerMode=Totals",
+            "MeasureType=Usage",
+            "LastNGames=10",
+            "Location=Home",
+            "Outcome=W",
+            "OpponentTeamID=1610612738",
+        ] {
+            assert!(url.contains(expected), "{} missing from {}", expected, url);
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use crate::error::FetchError;
+use crate::{error::FetchError, query::StatsQuery, Endpoint};
 
-use csv::Writer;
-use serde::Deserialize;
-use serde_json::Value;
-use std::{fs::File, path::PathBuf};
+use csv::{Writer, WriterBuilder};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::HashSet, fs::File, path::PathBuf};
 
 #[derive(Deserialize, Debug)]
 #[allow(non_snake_case)]
@@ -21,6 +21,131 @@ pub struct ResultSetsData {
     pub rowSet: Vec<Vec<Value>>,
 }
 
+impl ResultSetsFetchedResponse {
+    /// The first result set, which is the only one the league dash
+    /// endpoints return.
+    pub fn first_result_set(&self) -> Result<&ResultSetsData, FetchError> {
+        self.resultSets
+            .first()
+            .ok_or_else(|| FetchError::MissingResultSet("<first>".to_string()))
+    }
+
+    /// The result set called `name`, for endpoints that return several.
+    pub fn result_set(&self, name: &str) -> Result<&ResultSetsData, FetchError> {
+        self.resultSets
+            .iter()
+            .find(|set| set.name == name)
+            .ok_or_else(|| FetchError::MissingResultSet(name.to_string()))
+    }
+}
+
+impl ResultSetsData {
+    /// Maps each row onto `T` by header name. Fails before reading any rows
+    /// if a column of `T` is missing. Columns `T` doesn't know are dropped;
+    /// the schema check already reports them as drift.
+    pub fn rows<T: ResultSetRow>(&self) -> Result<Vec<T>, FetchError> {
+        let missing = missing_columns(&self.headers, T::COLUMNS);
+        if !missing.is_empty() {
+            return Err(FetchError::MissingColumns {
+                result_set: self.name.clone(),
+                missing,
+            });
+        }
+
+        self.rowSet
+            .iter()
+            .map(|row| {
+                let record: Map<String, Value> = self
+                    .headers
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().cloned())
+                    .collect();
+                serde_json::from_value(Value::Object(record)).map_err(FetchError::from)
+            })
+            .collect()
+    }
+}
+
+/// A typed row of a stats.nba.com result set. `COLUMNS` are the header
+/// names, in the order the endpoint sends them.
+pub trait ResultSetRow: DeserializeOwned + Serialize {
+    const COLUMNS: &'static [&'static str];
+}
+
+/// Declares a [`ResultSetRow`] struct, pairing each header with the field it
+/// maps to so the column list and the struct can't drift apart.
+macro_rules! result_set_row {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $($column:literal => $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        pub struct $name {
+            $(
+                #[serde(rename = $column)]
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::result_sets::ResultSetRow for $name {
+            const COLUMNS: &'static [&'static str] = &[$($column),*];
+        }
+    };
+}
+pub(crate) use result_set_row;
+
+pub(crate) fn missing_columns(headers: &[String], expected: &[&str]) -> Vec<String> {
+    let present: HashSet<&str> = headers.iter().map(String::as_str).collect();
+    expected
+        .iter()
+        .filter(|c| !present.contains(*c))
+        .map(|c| c.to_string())
+        .collect()
+}
+
+/// Writes `set` as `T` rows, unless `query` asked `endpoint` for a measure
+/// type other than the one `T` describes. Those responses have other
+/// columns, so they are written as sent.
+pub fn write_result_set<T: ResultSetRow>(
+    set: &ResultSetsData,
+    endpoint: Endpoint,
+    query: &StatsQuery,
+    path: PathBuf,
+) -> Result<(), FetchError> {
+    if endpoint.overrides_measure_type(query) {
+        write_vector_to_csv(Some(set.headers.clone()), set.rowSet.clone(), path)
+    } else {
+        write_rows_to_csv(&set.rows::<T>()?, path)
+    }
+}
+
+/// Writes typed rows with a header row taken from their column names.
+pub fn write_rows_to_csv<T: ResultSetRow>(rows: &[T], path: PathBuf) -> Result<(), FetchError> {
+    write_csv_with_header(T::COLUMNS, rows, path)
+}
+
+/// Writes `rows` under a header row of `columns`, which must name `T`'s
+/// fields in order. The header is written even when there are no rows, so
+/// every run's file has the same columns.
+pub fn write_csv_with_header<T: Serialize>(
+    columns: &[&str],
+    rows: impl IntoIterator<Item = T>,
+    path: PathBuf,
+) -> Result<(), FetchError> {
+    let file = File::create(path)?;
+    let mut wtr = WriterBuilder::new().has_headers(false).from_writer(file);
+    wtr.write_record(columns)?;
+    for row in rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
 pub fn write_vector_to_csv(
     headers: Option<Vec<String>>,
     rows: Vec<Vec<Value>>,
@@ -51,3 +176,69 @@ pub fn write_vector_to_csv(
     wtr.flush()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    result_set_row! {
+        pub struct TestRow {
+            "TEAM_ID" => team_id: i64,
+            "TEAM_NAME" => team_name: String,
+            "W_PCT" => w_pct: Option<f64>,
+        }
+    }
+
+    fn data(headers: &[&str], rows: Vec<Vec<Value>>) -> ResultSetsData {
+        ResultSetsData {
+            name: "LeagueDashTeamStats".to_string(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rowSet: rows,
+        }
+    }
+
+    #[test]
+    fn rows_map_by_header_name() {
+        let data = data(
+            &["W_PCT", "TEAM_ID", "TEAM_NAME"],
+            vec![
+                vec![json!(0.444), json!(1610612737), json!("Atlanta Hawks")],
+                vec![Value::Null, json!(1610612738), json!("Boston Celtics")],
+            ],
+        );
+        let rows: Vec<TestRow> = data.rows().unwrap();
+        assert_eq!(rows[0].team_id, 1610612737);
+        assert_eq!(rows[0].w_pct, Some(0.444));
+        assert_eq!(rows[1].team_name, "Boston Celtics");
+        assert_eq!(rows[1].w_pct, None);
+    }
+
+    #[test]
+    fn missing_columns_fail_and_added_ones_are_dropped() {
+        let data = data(&["TEAM_ID", "TEAM_CITY", "NEW_STAT"], vec![]);
+        let error = data.rows::<TestRow>().unwrap_err();
+        match &error {
+            FetchError::MissingColumns { missing, .. } => {
+                assert_eq!(missing, &["TEAM_NAME", "W_PCT"]);
+            }
+            other => panic!("expected missing columns, got {:?}", other),
+        }
+        let message = error.to_string();
+        assert!(message.contains("LeagueDashTeamStats"));
+        assert!(message.contains("W_PCT"));
+
+        let data = self::data(
+            &["TEAM_ID", "NEW_STAT", "TEAM_NAME", "W_PCT"],
+            vec![vec![
+                json!(1610612737),
+                json!(7),
+                json!("Atlanta Hawks"),
+                json!(0.5),
+            ]],
+        );
+        let rows: Vec<TestRow> = data.rows().unwrap();
+        assert_eq!(rows[0].team_name, "Atlanta Hawks");
+        assert_eq!(rows[0].w_pct, Some(0.5));
+    }
+}
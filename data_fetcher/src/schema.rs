@@ -0,0 +1,328 @@
+use crate::{transport::fixture_file_name, Endpoint};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tracing::{info, warn};
+
+pub fn schemas_file_path() -> PathBuf {
+    // "../data/schemas"
+    Endpoint::data_file_path().join("schemas")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonType {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => JsonType::Null,
+            Value::Bool(_) => JsonType::Bool,
+            Value::Number(_) => JsonType::Number,
+            Value::String(_) => JsonType::String,
+            Value::Array(_) => JsonType::Array,
+            Value::Object(_) => JsonType::Object,
+        }
+    }
+}
+
+/// Every path in a response and the types seen there. Array elements share
+/// the path `parent[]`, and result sets are flattened to one path per
+/// column, e.g. `resultSets[].PLAYER_ID`.
+pub type Shape = BTreeMap<String, BTreeSet<JsonType>>;
+
+pub fn shape_of(value: &Value) -> Shape {
+    let mut shape = Shape::new();
+    walk(value, "", &mut shape);
+    shape
+}
+
+fn walk(value: &Value, path: &str, shape: &mut Shape) {
+    if !path.is_empty() {
+        shape
+            .entry(path.to_string())
+            .or_default()
+            .insert(JsonType::of(value));
+    }
+    match value {
+        Value::Object(map) => {
+            if let (Some(Value::Array(headers)), Some(Value::Array(rows))) =
+                (map.get("headers"), map.get("rowSet"))
+            {
+                walk_result_set(headers, rows, path, shape);
+                return;
+            }
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                walk(child, &child_path, shape);
+            }
+        }
+        Value::Array(items) => {
+            let child_path = format!("{}[]", path);
+            for item in items {
+                walk(item, &child_path, shape);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn walk_result_set(headers: &[Value], rows: &[Value], path: &str, shape: &mut Shape) {
+    for (i, header) in headers.iter().enumerate() {
+        let column = format!("{}.{}", path, header.as_str().unwrap_or_default());
+        let types = shape.entry(column).or_default();
+        for row in rows {
+            if let Some(cell) = row.get(i) {
+                types.insert(JsonType::of(cell));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retyped {
+    pub path: String,
+    pub expected: BTreeSet<JsonType>,
+    pub found: BTreeSet<JsonType>,
+}
+
+/// How a response differs from the shape recorded for its endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaDrift {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub retyped: Vec<Retyped>,
+}
+
+impl SchemaDrift {
+    /// Compares `found` against `expected`. A field counts as retyped only if
+    /// it holds a type never recorded for it; `null` is accepted anywhere,
+    /// since most upstream fields are null in some rows.
+    pub fn between(expected: &Shape, found: &Shape) -> Self {
+        let mut drift = SchemaDrift::default();
+        for (path, found_types) in found {
+            match expected.get(path) {
+                None => drift.added.push(path.clone()),
+                Some(expected_types) => {
+                    let unexpected = found_types
+                        .iter()
+                        .any(|t| *t != JsonType::Null && !expected_types.contains(t));
+                    if unexpected {
+                        drift.retyped.push(Retyped {
+                            path: path.clone(),
+                            expected: expected_types.clone(),
+                            found: found_types.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        drift.removed = expected
+            .keys()
+            .filter(|path| !found.contains_key(*path))
+            .cloned()
+            .collect();
+        drift
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.retyped.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn types(set: &BTreeSet<JsonType>) -> String {
+            let names: Vec<_> = set
+                .iter()
+                .map(|t| format!("{:?}", t).to_lowercase())
+                .collect();
+            names.join("|")
+        }
+
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("added {}", self.added.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        if !self.retyped.is_empty() {
+            let retyped: Vec<_> = self
+                .retyped
+                .iter()
+                .map(|r| format!("{} ({} -> {})", r.path, types(&r.expected), types(&r.found)))
+                .collect();
+            parts.push(format!("retyped {}", retyped.join(", ")));
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// The shape each endpoint's response is expected to have, one JSON file per
+/// endpoint, and the drift found by checks since the registry was created.
+#[derive(Debug)]
+pub struct SchemaRegistry {
+    dir: PathBuf,
+    drift: Mutex<HashMap<Endpoint, SchemaDrift>>,
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new(schemas_file_path())
+    }
+}
+
+impl SchemaRegistry {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        SchemaRegistry {
+            dir: dir.into(),
+            drift: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn path_for(&self, endpoint: Endpoint) -> PathBuf {
+        self.dir.join(fixture_file_name(endpoint))
+    }
+
+    /// The recorded shape, or `None` if nothing was recorded for `endpoint`.
+    pub fn expected(&self, endpoint: Endpoint) -> io::Result<Option<Shape>> {
+        match fs::read(self.path_for(endpoint)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn record(&self, endpoint: Endpoint, shape: &Shape) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_vec_pretty(shape)?;
+        fs::write(self.path_for(endpoint), json)?;
+        info!("Recorded schema for {:?}", endpoint);
+        Ok(())
+    }
+
+    /// Records the shape of a saved response, e.g. a raw file from
+    /// `fetched_data_output_as_string`.
+    pub fn record_from_file(&self, endpoint: Endpoint, path: &Path) -> io::Result<()> {
+        let value: Value = serde_json::from_slice(&fs::read(path)?)?;
+        self.record(endpoint, &shape_of(&value))
+    }
+
+    /// Compares `value` against the recorded shape. Drift is logged and kept
+    /// for [`take_drift`](Self::take_drift) rather than failing the fetch;
+    /// endpoints with nothing recorded are not checked.
+    pub fn check(&self, endpoint: Endpoint, value: &Value) -> Option<SchemaDrift> {
+        let expected = match self.expected(endpoint) {
+            Ok(Some(expected)) => expected,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!("Could not read schema for {:?}: {}", endpoint, e);
+                return None;
+            }
+        };
+        let drift = SchemaDrift::between(&expected, &shape_of(value));
+        if drift.is_empty() {
+            return None;
+        }
+        warn!("Schema drift in {:?}: {}", endpoint, drift);
+        self.drift.lock().unwrap().insert(endpoint, drift.clone());
+        Some(drift)
+    }
+
+    pub fn take_drift(&self, endpoint: Endpoint) -> Option<SchemaDrift> {
+        self.drift.lock().unwrap().remove(&endpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::env;
+
+    #[test]
+    fn drift_reports_added_removed_and_retyped_fields() {
+        let expected = shape_of(&json!({
+            "pageProps": {
+                "oddsTables": [{ "gameRows": [{ "homeOdds": -110, "book": "bet365" }] }]
+            },
+            "resultSets": [{
+                "name": "PlayerIndex",
+                "headers": ["PERSON_ID", "PTS"],
+                "rowSet": [[1, 12.5], [2, null]]
+            }]
+        }));
+        let found = shape_of(&json!({
+            "pageProps": {
+                "oddsTables": [{ "gameRows": [{ "homeOdds": "-110", "live": true }] }]
+            },
+            "resultSets": [{
+                "name": "PlayerIndex",
+                "headers": ["PERSON_ID", "PTS", "NEW_STAT"],
+                "rowSet": [[1, null, 3]]
+            }]
+        }));
+
+        let drift = SchemaDrift::between(&expected, &found);
+        assert_eq!(
+            drift.added,
+            vec![
+                "pageProps.oddsTables[].gameRows[].live",
+                "resultSets[].NEW_STAT"
+            ]
+        );
+        assert_eq!(
+            drift.removed,
+            vec!["pageProps.oddsTables[].gameRows[].book"]
+        );
+        assert_eq!(drift.retyped.len(), 1);
+        assert_eq!(
+            drift.retyped[0].path,
+            "pageProps.oddsTables[].gameRows[].homeOdds"
+        );
+        assert!(drift.to_string().contains("homeOdds (number -> string)"));
+    }
+
+    #[test]
+    fn registry_checks_against_recorded_shapes() {
+        let dir = env::temp_dir().join("nba_schema_registry");
+        let _ = fs::remove_dir_all(&dir);
+        let registry = SchemaRegistry::new(&dir);
+
+        let response = json!({ "leagueSchedule": { "gameDates": [] } });
+        assert!(registry
+            .check(Endpoint::SeasonSchedule, &response)
+            .is_none());
+
+        registry
+            .record(Endpoint::SeasonSchedule, &shape_of(&response))
+            .unwrap();
+        assert!(registry
+            .check(Endpoint::SeasonSchedule, &response)
+            .is_none());
+
+        let changed = json!({ "leagueSchedule": { "gameDates": {} } });
+        assert!(registry.check(Endpoint::SeasonSchedule, &changed).is_some());
+        assert!(registry.take_drift(Endpoint::SeasonSchedule).is_some());
+        assert!(registry.take_drift(Endpoint::SeasonSchedule).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{error::FetchError, fetch_data, Endpoint};
+use crate::{error::FetchError, fetch_data, Endpoint, FetchContext};
 use std::{
     fs::File,
     io::{BufWriter, Write},
@@ -29,19 +29,19 @@ struct GameDatesData {
     games: Vec<Value>,
 }
 
-pub async fn fetch_season_schedule() -> Result<(), FetchError> {
+pub async fn fetch_season_schedule(ctx: &FetchContext) -> Result<(), FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::SeasonSchedule.url();
+    let endpoint = Endpoint::SeasonSchedule.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::SeasonSchedule.file_name();
 
     // Fetch Data
-    let data: LeagueScheduleFetchedResponse = fetch_data(endpoint, file_name).await?;
+    let data: LeagueScheduleFetchedResponse =
+        fetch_data(ctx, Endpoint::SeasonSchedule, &endpoint, file_name).await?;
 
     let game_dates = data.leagueSchedule.gameDates;
 
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
     save_file_path.set_extension("json");
 
     let file = File::create(save_file_path)?;
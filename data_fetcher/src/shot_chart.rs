@@ -0,0 +1,348 @@
+use crate::{
+    error::FetchError,
+    fetch_data,
+    query::StatsQuery,
+    result_sets::{
+        result_set_row, write_csv_with_header, write_rows_to_csv, ResultSetsFetchedResponse,
+    },
+    Endpoint, FetchContext, OutputDirs,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+result_set_row! {
+    /// One shot from `shotchartdetail`. `LOC_X`/`LOC_Y` are tenths of a
+    /// foot from the basket, `SHOT_DISTANCE` is in feet, and `HTM`/`VTM`
+    /// are the home and visiting teams' abbreviations.
+    pub struct ShotChartRow {
+        "GRID_TYPE" => grid_type: String,
+        "GAME_ID" => game_id: String,
+        "GAME_EVENT_ID" => game_event_id: i64,
+        "PLAYER_ID" => player_id: i64,
+        "PLAYER_NAME" => player_name: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_NAME" => team_name: String,
+        "PERIOD" => period: i64,
+        "MINUTES_REMAINING" => minutes_remaining: i64,
+        "SECONDS_REMAINING" => seconds_remaining: i64,
+        "EVENT_TYPE" => event_type: String,
+        "ACTION_TYPE" => action_type: String,
+        "SHOT_TYPE" => shot_type: String,
+        "SHOT_ZONE_BASIC" => shot_zone_basic: String,
+        "SHOT_ZONE_AREA" => shot_zone_area: String,
+        "SHOT_ZONE_RANGE" => shot_zone_range: String,
+        "SHOT_DISTANCE" => shot_distance: i64,
+        "LOC_X" => loc_x: i64,
+        "LOC_Y" => loc_y: i64,
+        "SHOT_ATTEMPTED_FLAG" => shot_attempted_flag: i64,
+        "SHOT_MADE_FLAG" => shot_made_flag: i64,
+        "GAME_DATE" => game_date: String,
+        "HTM" => htm: String,
+        "VTM" => vtm: String,
+    }
+}
+
+const SHOT_CHART: &str = "Shot_Chart_Detail";
+
+/// The raw response with a team's opponents' shots, next to its own.
+const SHOTS_AGAINST_FILE_NAME: &str = "fetched_ShotChart_against_data";
+
+/// Where a shot was taken, coarser than `SHOT_ZONE_BASIC`: the rim is the
+/// restricted area and the two corner threes are one zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ShotZone {
+    Rim,
+    Paint,
+    MidRange,
+    CornerThree,
+    AboveBreakThree,
+    Backcourt,
+}
+
+impl ShotZone {
+    /// Maps a `SHOT_ZONE_BASIC` value; `None` for one this doesn't know.
+    pub fn from_basic(zone: &str) -> Option<Self> {
+        match zone {
+            "Restricted Area" => Some(ShotZone::Rim),
+            "In The Paint (Non-RA)" => Some(ShotZone::Paint),
+            "Mid-Range" => Some(ShotZone::MidRange),
+            "Left Corner 3" | "Right Corner 3" => Some(ShotZone::CornerThree),
+            "Above the Break 3" => Some(ShotZone::AboveBreakThree),
+            "Backcourt" => Some(ShotZone::Backcourt),
+            _ => None,
+        }
+    }
+}
+
+/// A team's shooting from one zone, either its own (`side` is `for`) or its
+/// opponents' (`against`). `frequency` is the share of that side's attempts
+/// taken from the zone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShotZoneSplit {
+    pub team_id: i64,
+    pub side: String,
+    pub zone: ShotZone,
+    pub fga: u32,
+    pub fgm: u32,
+    pub fg_pct: f64,
+    pub frequency: f64,
+}
+
+impl ShotZoneSplit {
+    /// The fields, as the header of `fetched_ShotChart_zones.csv`.
+    pub const COLUMNS: [&'static str; 7] = [
+        "team_id",
+        "side",
+        "zone",
+        "fga",
+        "fgm",
+        "fg_pct",
+        "frequency",
+    ];
+}
+
+/// Aggregates `shots` into [`ShotZoneSplit`]s, ordered by team, side and
+/// zone. A shot's opponent is the other team seen in the same game, so
+/// `against` rows need both teams' shots; shots in unknown zones are left
+/// out.
+pub fn shot_zone_splits(shots: &[ShotChartRow]) -> Vec<ShotZoneSplit> {
+    let mut teams_in_game: HashMap<&str, BTreeSet<i64>> = HashMap::new();
+    for shot in shots {
+        teams_in_game
+            .entry(&shot.game_id)
+            .or_default()
+            .insert(shot.team_id);
+    }
+
+    // (team, side, zone) -> (attempts, makes)
+    let mut counts: BTreeMap<(i64, &str, ShotZone), (u32, u32)> = BTreeMap::new();
+    for shot in shots.iter().filter(|s| s.shot_attempted_flag == 1) {
+        let Some(zone) = ShotZone::from_basic(&shot.shot_zone_basic) else {
+            continue;
+        };
+        let made = u32::from(shot.shot_made_flag == 1);
+        let mut sides = vec![(shot.team_id, "for")];
+        let others: Vec<i64> = teams_in_game[shot.game_id.as_str()]
+            .iter()
+            .copied()
+            .filter(|&t| t != shot.team_id)
+            .collect();
+        if let [opponent] = others[..] {
+            sides.push((opponent, "against"));
+        }
+        for (team_id, side) in sides {
+            let count = counts.entry((team_id, side, zone)).or_default();
+            count.0 += 1;
+            count.1 += made;
+        }
+    }
+
+    let mut attempts: HashMap<(i64, &str), u32> = HashMap::new();
+    for ((team_id, side, _), (fga, _)) in &counts {
+        *attempts.entry((*team_id, side)).or_default() += fga;
+    }
+    counts
+        .into_iter()
+        .map(|((team_id, side, zone), (fga, fgm))| ShotZoneSplit {
+            team_id,
+            side: side.to_string(),
+            zone,
+            fga,
+            fgm,
+            fg_pct: f64::from(fgm) / f64::from(fga),
+            frequency: f64::from(fga) / f64::from(attempts[&(team_id, side)]),
+        })
+        .collect()
+}
+
+/// The prepared zone table next to the shots, `fetched_ShotChart_zones.csv`.
+pub fn shot_zones_path(dirs: &OutputDirs) -> PathBuf {
+    dirs.prepared.join("fetched_ShotChart_zones.csv")
+}
+
+/// Fetches the shots matching `query`, keeping the response as `file_name`.
+async fn fetch_shots(
+    ctx: &FetchContext,
+    query: &StatsQuery,
+    file_name: &str,
+) -> Result<Vec<ShotChartRow>, FetchError> {
+    let endpoint = Endpoint::ShotChart.url(query);
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::ShotChart, &endpoint, file_name).await?;
+    data.result_set(SHOT_CHART)?.rows()
+}
+
+/// Fetches every shot matching `ctx.query` (the whole league unless it
+/// names a team) and writes them along with their [`shot_zone_splits`].
+///
+/// A query naming a team only returns that team's shots, so its opponents'
+/// are fetched as well, with the teams swapped, for the `against` rows.
+/// Only the named team's splits are written then.
+pub async fn fetch_shot_chart(ctx: &FetchContext) -> Result<(), FetchError> {
+    // File Name for future reference
+    let file_name = Endpoint::ShotChart.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+    // Fetch Data
+    let shots = fetch_shots(ctx, &ctx.query, file_name).await?;
+    write_rows_to_csv(&shots, save_file_path)?;
+
+    let splits = match ctx.query.team_id() {
+        None => shot_zone_splits(&shots),
+        Some(team_id) => {
+            let against = fetch_shots(
+                ctx,
+                &ctx.query.with_teams_swapped(),
+                SHOTS_AGAINST_FILE_NAME,
+            )
+            .await?;
+            shot_zone_splits(&[shots, against].concat())
+                .into_iter()
+                .filter(|split| split.team_id == i64::from(team_id))
+                .collect()
+        }
+    };
+    write_csv_with_header(&ShotZoneSplit::COLUMNS, splits, shot_zones_path(&ctx.dirs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock_server::{MockResponse, MockServer},
+        transport::FixtureTransport,
+    };
+    use serde_json::Value;
+    use std::{env, fs, sync::Arc};
+
+    const DET: i64 = 1610612765;
+    const HOU: i64 = 1610612745;
+
+    #[tokio::test]
+    async fn shots_are_split_by_zone_for_and_against() {
+        let dir = env::temp_dir().join("nba_shot_chart");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_shot_chart(&ctx).await.unwrap();
+
+        let mut reader =
+            csv::Reader::from_path(dirs.prepared.join("fetched_ShotChart_data.csv")).unwrap();
+        let shots: Vec<ShotChartRow> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(shots.len(), 12);
+        assert_eq!((shots[4].loc_x, shots[4].shot_distance), (-224, 22));
+
+        let mut reader = csv::Reader::from_path(shot_zones_path(&dirs)).unwrap();
+        assert_eq!(reader.headers().unwrap(), &ShotZoneSplit::COLUMNS[..]);
+        let splits: Vec<ShotZoneSplit> = reader.deserialize().map(Result::unwrap).collect();
+        let find = |team_id, side: &str, zone| {
+            splits
+                .iter()
+                .find(|s| s.team_id == team_id && s.side == side && s.zone == zone)
+                .unwrap()
+        };
+
+        let det_rim = find(DET, "for", ShotZone::Rim);
+        assert_eq!((det_rim.fga, det_rim.fgm), (3, 2));
+        assert!((det_rim.frequency - 0.5).abs() < 1e-9);
+        // Detroit's shots are Houston's defense.
+        assert_eq!(
+            find(HOU, "against", ShotZone::Rim),
+            &ShotZoneSplit {
+                team_id: HOU,
+                side: "against".to_string(),
+                ..det_rim.clone()
+            }
+        );
+        let corners = find(DET, "against", ShotZone::CornerThree);
+        assert_eq!((corners.fga, corners.fgm, corners.fg_pct), (1, 0, 0.0));
+        let total: u32 = splits
+            .iter()
+            .filter(|s| s.team_id == HOU && s.side == "for")
+            .map(|s| s.fga)
+            .sum();
+        assert_eq!(total, 6);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// The seed response with only `team_id`'s shots.
+    fn seed_shots_of(team_id: i64) -> MockResponse {
+        let path = Endpoint::data_file_path()
+            .join("seed_data")
+            .join("shotchartdetail.json");
+        let mut seed: Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        let rows = seed["resultSets"][0]["rowSet"].as_array_mut().unwrap();
+        rows.retain(|row| row[5] == team_id);
+        MockResponse::ok(seed.to_string())
+    }
+
+    #[tokio::test]
+    async fn a_teams_opponents_shots_are_fetched_for_its_against_splits() {
+        let server = MockServer::start().await;
+        server
+            .route(&format!("OpponentTeamID={}", DET), vec![seed_shots_of(HOU)])
+            .route(&format!("TeamID={}", DET), vec![seed_shots_of(DET)]);
+        let dir = env::temp_dir().join("nba_shot_chart_team");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::new().with_team_id(DET as u32), dirs.clone())
+            .with_transport(server.transport());
+
+        fetch_shot_chart(&ctx).await.unwrap();
+
+        let mut reader = csv::Reader::from_path(shot_zones_path(&dirs)).unwrap();
+        let splits: Vec<ShotZoneSplit> = reader.deserialize().map(Result::unwrap).collect();
+        assert!(splits.iter().all(|s| s.team_id == DET));
+        let against: u32 = splits
+            .iter()
+            .filter(|s| s.side == "against")
+            .map(|s| s.fga)
+            .sum();
+        assert_eq!(against, 6);
+        let rim = splits
+            .iter()
+            .find(|s| s.side == "for" && s.zone == ShotZone::Rim)
+            .unwrap();
+        assert_eq!((rim.fga, rim.fgm), (3, 2));
+        assert!(dirs.raw.join("fetched_ShotChart_against_data.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn no_shots_still_write_the_zone_header() {
+        let server = MockServer::start().await;
+        server.route("/stats/shotchartdetail", vec![seed_shots_of(0)]);
+        let dir = env::temp_dir().join("nba_shot_chart_empty");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(server.transport());
+
+        fetch_shot_chart(&ctx).await.unwrap();
+
+        let zones = fs::read_to_string(shot_zones_path(&dirs)).unwrap();
+        assert_eq!(zones, format!("{}\n", ShotZoneSplit::COLUMNS.join(",")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn basic_zones_map_to_coarse_ones() {
+        assert_eq!(
+            ShotZone::from_basic("Right Corner 3"),
+            Some(ShotZone::CornerThree)
+        );
+        assert_eq!(ShotZone::from_basic("Restricted Area"), Some(ShotZone::Rim));
+        assert_eq!(ShotZone::from_basic("Somewhere"), None);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{Endpoint, OutputDirs};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Snapshot directories are named by the UTC time the run started, in a
+/// format that sorts chronologically and is valid on Windows.
+const SNAPSHOT_DIR_FORMAT: &str = "%Y-%m-%dT%H%M%SZ";
+
+/// File in the snapshots directory holding the name of the latest snapshot.
+const LATEST_POINTER: &str = "LATEST";
+
+pub fn snapshots_file_path() -> PathBuf {
+    // "../data/snapshots"
+    Endpoint::data_file_path().join("snapshots")
+}
+
+/// A point-in-time copy of everything one run fetched, so models can be
+/// trained on the data exactly as it looked before a game.
+#[derive(Debug)]
+pub struct Snapshot {
+    taken_at: DateTime<Utc>,
+    root: PathBuf,
+    dirs: OutputDirs,
+}
+
+impl Snapshot {
+    pub fn begin(taken_at: DateTime<Utc>) -> io::Result<Self> {
+        Self::begin_in(&snapshots_file_path(), taken_at)
+    }
+
+    pub fn begin_in(snapshots_dir: &Path, taken_at: DateTime<Utc>) -> io::Result<Self> {
+        let root = snapshots_dir.join(taken_at.format(SNAPSHOT_DIR_FORMAT).to_string());
+        let dirs = OutputDirs::under(&root);
+        dirs.create()?;
+        Ok(Snapshot {
+            taken_at,
+            root,
+            dirs,
+        })
+    }
+
+    pub fn taken_at(&self) -> DateTime<Utc> {
+        self.taken_at
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn dirs(&self) -> &OutputDirs {
+        &self.dirs
+    }
+
+    /// Copies the snapshot, manifest included, over `latest` and points the `LATEST` file in the
+    /// snapshots directory at it.
+    pub fn promote_to_latest(&self, latest: &OutputDirs) -> io::Result<()> {
+        latest.create()?;
+        copy_files(&self.dirs.raw, &latest.raw)?;
+        copy_files(&self.dirs.prepared, &latest.prepared)?;
+        if self.dirs.manifest().exists() {
+            fs::copy(self.dirs.manifest(), latest.manifest())?;
+        }
+
+        let snapshots_dir = self.root.parent().expect("snapshot has a parent directory");
+        let name = self.root.file_name().expect("snapshot has a name");
+        fs::write(
+            snapshots_dir.join(LATEST_POINTER),
+            name.to_string_lossy().as_bytes(),
+        )?;
+        info!("Promoted snapshot {:?} to latest", name);
+        Ok(())
+    }
+}
+
+fn copy_files(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// The snapshot the `LATEST` pointer refers to, if any run has completed.
+pub fn latest_snapshot(snapshots_dir: &Path) -> io::Result<Option<OutputDirs>> {
+    match fs::read_to_string(snapshots_dir.join(LATEST_POINTER)) {
+        Ok(name) => Ok(Some(OutputDirs::under(&snapshots_dir.join(name.trim())))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The most recent snapshot taken at or before `as_of`. Use this when
+/// building features for a game so that only data available before tip-off
+/// is seen.
+pub fn snapshot_as_of(
+    snapshots_dir: &Path,
+    as_of: DateTime<Utc>,
+) -> io::Result<Option<OutputDirs>> {
+    let mut best: Option<(DateTime<Utc>, PathBuf)> = None;
+    for entry in fs::read_dir(snapshots_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name();
+        let Ok(taken_at) =
+            NaiveDateTime::parse_from_str(&name.to_string_lossy(), SNAPSHOT_DIR_FORMAT)
+        else {
+            continue;
+        };
+        let taken_at = taken_at.and_utc();
+        if taken_at <= as_of && best.as_ref().is_none_or(|(t, _)| taken_at > *t) {
+            best = Some((taken_at, entry.path()));
+        }
+    }
+    Ok(best.map(|(_, path)| OutputDirs::under(&path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fetch_endpoint, pipeline::FetchOutcome, query::StatsQuery, transport::FixtureTransport,
+        FetchContext,
+    };
+    use chrono::TimeZone;
+    use std::{env, sync::Arc};
+
+    #[test]
+    fn snapshots_are_dated_and_promoted() {
+        let dir = env::temp_dir().join("nba_snapshot_test");
+        let _ = fs::remove_dir_all(&dir);
+        let snapshots_dir = dir.join("snapshots");
+        let latest = OutputDirs::under(&dir);
+
+        let first = Utc.with_ymd_and_hms(2024, 11, 12, 15, 0, 0).unwrap();
+        let second = Utc.with_ymd_and_hms(2024, 11, 13, 15, 0, 0).unwrap();
+        for (taken_at, body) in [(first, "monday"), (second, "tuesday")] {
+            let snapshot = Snapshot::begin_in(&snapshots_dir, taken_at).unwrap();
+            fs::write(snapshot.dirs().prepared.join("odds.csv"), body).unwrap();
+            snapshot.promote_to_latest(&latest).unwrap();
+        }
+
+        assert!(snapshots_dir.join("2024-11-12T150000Z").is_dir());
+        assert_eq!(
+            fs::read_to_string(latest.prepared.join("odds.csv")).unwrap(),
+            "tuesday"
+        );
+        let pointed = latest_snapshot(&snapshots_dir).unwrap().unwrap();
+        assert!(pointed
+            .prepared
+            .ends_with("2024-11-13T150000Z/prepared_data"));
+
+        let before_second = Utc.with_ymd_and_hms(2024, 11, 13, 1, 0, 0).unwrap();
+        let as_of = snapshot_as_of(&snapshots_dir, before_second)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(as_of.prepared.join("odds.csv")).unwrap(),
+            "monday"
+        );
+        let too_early = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert!(snapshot_as_of(&snapshots_dir, too_early).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn promoted_raw_responses_are_found_in_latest() {
+        let dir = env::temp_dir().join("nba_snapshot_raw_responses");
+        let _ = fs::remove_dir_all(&dir);
+        let latest = OutputDirs::under(&dir);
+        let snapshot = Snapshot::begin_in(&dir.join("snapshots"), Utc::now()).unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), snapshot.dirs().clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        let endpoints = [Endpoint::PlayerOnOff, Endpoint::Standings];
+        for endpoint in endpoints {
+            assert!(matches!(
+                fetch_endpoint(endpoint, &ctx).await,
+                FetchOutcome::Success
+            ));
+        }
+        snapshot.promote_to_latest(&latest).unwrap();
+
+        for endpoint in endpoints {
+            let path = latest.raw_response(endpoint);
+            assert!(
+                path.exists(),
+                "{:?} has no raw response at {:?}",
+                endpoint,
+                path
+            );
+            assert_eq!(
+                fs::read(&path).unwrap(),
+                fs::read(snapshot.dirs().raw_response(endpoint)).unwrap()
+            );
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
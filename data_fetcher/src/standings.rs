@@ -0,0 +1,194 @@
+use crate::{
+    error::FetchError,
+    fetch_data,
+    result_sets::{result_set_row, write_rows_to_csv, ResultSetsFetchedResponse},
+    Endpoint, FetchContext,
+};
+
+result_set_row! {
+    /// One team's line in `leaguestandingsv3`. Records such as `HOME`, `L10`
+    /// or `vsEast` are `W-L` strings; month records are null before the
+    /// month has a game. `ClinchIndicator` is a suffix like ` - x` (see
+    /// [`StandingsRow::clinch_code`]), and the `Clinched*`/`Eliminated*`
+    /// columns are `0`/`1` flags. `ClinchedPlayIn`, `PlayoffSeeding` and
+    /// `ClinchedPostSeason` are null until they apply.
+    pub struct StandingsRow {
+        "LeagueID" => league_id: String,
+        "SeasonID" => season_id: String,
+        "TeamID" => team_id: i64,
+        "TeamCity" => team_city: String,
+        "TeamName" => team_name: String,
+        "TeamSlug" => team_slug: String,
+        "Conference" => conference: String,
+        "ConferenceRecord" => conference_record: String,
+        "PlayoffRank" => playoff_rank: i64,
+        "ClinchIndicator" => clinch_indicator: Option<String>,
+        "Division" => division: String,
+        "DivisionRecord" => division_record: String,
+        "DivisionRank" => division_rank: i64,
+        "WINS" => wins: i64,
+        "LOSSES" => losses: i64,
+        "WinPCT" => win_pct: f64,
+        "LeagueRank" => league_rank: Option<i64>,
+        "Record" => record: String,
+        "HOME" => home: String,
+        "ROAD" => road: String,
+        "L10" => l10: String,
+        "Last10Home" => last10_home: String,
+        "Last10Road" => last10_road: String,
+        "OT" => ot: String,
+        "ThreePTSOrLess" => three_pts_or_less: String,
+        "TenPTSOrMore" => ten_pts_or_more: String,
+        "LongHomeStreak" => long_home_streak: i64,
+        "strLongHomeStreak" => str_long_home_streak: String,
+        "LongRoadStreak" => long_road_streak: i64,
+        "strLongRoadStreak" => str_long_road_streak: String,
+        "LongWinStreak" => long_win_streak: i64,
+        "LongLossStreak" => long_loss_streak: i64,
+        "CurrentHomeStreak" => current_home_streak: i64,
+        "strCurrentHomeStreak" => str_current_home_streak: String,
+        "CurrentRoadStreak" => current_road_streak: i64,
+        "strCurrentRoadStreak" => str_current_road_streak: String,
+        "CurrentStreak" => current_streak: i64,
+        "strCurrentStreak" => str_current_streak: String,
+        "ConferenceGamesBack" => conference_games_back: f64,
+        "DivisionGamesBack" => division_games_back: f64,
+        "ClinchedConferenceTitle" => clinched_conference_title: i64,
+        "ClinchedDivisionTitle" => clinched_division_title: i64,
+        "ClinchedPlayoffBirth" => clinched_playoff_birth: i64,
+        "ClinchedPlayIn" => clinched_play_in: Option<i64>,
+        "EliminatedConference" => eliminated_conference: i64,
+        "EliminatedDivision" => eliminated_division: i64,
+        "AheadAtHalf" => ahead_at_half: String,
+        "BehindAtHalf" => behind_at_half: String,
+        "TiedAtHalf" => tied_at_half: String,
+        "AheadAtThird" => ahead_at_third: String,
+        "BehindAtThird" => behind_at_third: String,
+        "TiedAtThird" => tied_at_third: String,
+        "Score100PTS" => score_100_pts: String,
+        "OppScore100PTS" => opp_score_100_pts: String,
+        "OppOver500" => opp_over_500: String,
+        "LeadInFGPCT" => lead_in_fg_pct: String,
+        "LeadInReb" => lead_in_reb: String,
+        "FewerTurnovers" => fewer_turnovers: String,
+        "PointsPG" => points_pg: f64,
+        "OppPointsPG" => opp_points_pg: f64,
+        "DiffPointsPG" => diff_points_pg: f64,
+        "vsEast" => vs_east: String,
+        "vsAtlantic" => vs_atlantic: String,
+        "vsCentral" => vs_central: String,
+        "vsSoutheast" => vs_southeast: String,
+        "vsWest" => vs_west: String,
+        "vsNorthwest" => vs_northwest: String,
+        "vsPacific" => vs_pacific: String,
+        "vsSouthwest" => vs_southwest: String,
+        "Jan" => jan: Option<String>,
+        "Feb" => feb: Option<String>,
+        "Mar" => mar: Option<String>,
+        "Apr" => apr: Option<String>,
+        "May" => may: Option<String>,
+        "Jun" => jun: Option<String>,
+        "Jul" => jul: Option<String>,
+        "Aug" => aug: Option<String>,
+        "Sep" => sep: Option<String>,
+        "Oct" => oct: Option<String>,
+        "Nov" => nov: Option<String>,
+        "Dec" => dec: Option<String>,
+        "Score_80_Plus" => score_80_plus: String,
+        "Opp_Score_80_Plus" => opp_score_80_plus: String,
+        "Score_Below_80" => score_below_80: String,
+        "Opp_Score_Below_80" => opp_score_below_80: String,
+        "TotalPoints" => total_points: i64,
+        "OppTotalPoints" => opp_total_points: i64,
+        "DiffTotalPoints" => diff_total_points: i64,
+        "LeagueGamesBack" => league_games_back: f64,
+        "PlayoffSeeding" => playoff_seeding: Option<i64>,
+        "ClinchedPostSeason" => clinched_post_season: Option<i64>,
+        "NEUTRAL" => neutral: String,
+    }
+}
+
+impl StandingsRow {
+    /// The letter of `ClinchIndicator` without its ` - ` prefix: `x` for a
+    /// playoff spot, `y` a division, `z` the conference, `w` the play-in,
+    /// `o` eliminated. `None` while nothing is decided.
+    pub fn clinch_code(&self) -> Option<&str> {
+        let code = self
+            .clinch_indicator
+            .as_deref()?
+            .trim()
+            .trim_start_matches('-')
+            .trim();
+        (!code.is_empty()).then_some(code)
+    }
+}
+
+/// Parses a `W-L` record such as `HOME` or `L10` into `(wins, losses)`.
+pub fn parse_record(record: &str) -> Option<(u32, u32)> {
+    let (wins, losses) = record.trim().split_once('-')?;
+    Some((wins.parse().ok()?, losses.parse().ok()?))
+}
+
+pub async fn fetch_standings(ctx: &FetchContext) -> Result<(), FetchError> {
+    // API Endpoint
+    let endpoint = Endpoint::Standings.url(&ctx.query);
+    // File Name for future reference
+    let file_name = Endpoint::Standings.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+    // Fetch Data
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::Standings, &endpoint, file_name).await?;
+    let rows: Vec<StandingsRow> = data.result_set("Standings")?.rows()?;
+    write_rows_to_csv(&rows, save_file_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query::StatsQuery, transport::FixtureTransport, OutputDirs};
+    use std::{env, fs, sync::Arc};
+
+    #[test]
+    fn records_split_into_wins_and_losses() {
+        assert_eq!(parse_record("6-0"), Some((6, 0)));
+        assert_eq!(parse_record(" 41-41"), Some((41, 41)));
+        assert_eq!(parse_record("W 3"), None);
+    }
+
+    #[tokio::test]
+    async fn standings_keep_ranks_and_clinch_state() {
+        let dir = env::temp_dir().join("nba_standings");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        fetch_standings(&ctx).await.unwrap();
+
+        let mut reader =
+            csv::Reader::from_path(dirs.prepared.join("fetched_Standings_data.csv")).unwrap();
+        let rows: Vec<StandingsRow> = reader.deserialize().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 6);
+
+        let cavs = &rows[0];
+        assert_eq!(
+            (cavs.team_city.as_str(), cavs.playoff_rank),
+            ("Cleveland", 1)
+        );
+        assert_eq!(cavs.clinch_code(), Some("x"));
+        assert_eq!(parse_record(&cavs.home), Some((6, 0)));
+
+        let pistons = rows.iter().find(|r| r.team_id == 1610612765).unwrap();
+        assert_eq!(pistons.clinch_code(), None);
+        assert_eq!(pistons.division_rank, 3);
+        assert_eq!(pistons.conference_games_back, 7.0);
+        assert_eq!(pistons.str_current_streak, "L 1");
+        assert_eq!(parse_record(&pistons.l10), Some((4, 6)));
+        assert_eq!(pistons.jan, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
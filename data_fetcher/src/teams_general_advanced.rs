@@ -1,22 +1,77 @@
 use crate::{
     error::FetchError,
     fetch_data,
-    result_sets::{write_vector_to_csv, ResultSetsFetchedResponse},
-    Endpoint,
+    result_sets::{result_set_row, write_result_set, ResultSetsFetchedResponse},
+    Endpoint, FetchContext,
 };
 
-pub async fn fetch_teams_general_advanced() -> Result<(), FetchError> {
+result_set_row! {
+    /// One team from `leaguedashteamstats` with `MeasureType=Advanced`.
+    pub struct TeamAdvancedRow {
+        "TEAM_ID" => team_id: i64,
+        "TEAM_NAME" => team_name: String,
+        "GP" => gp: i64,
+        "W" => w: i64,
+        "L" => l: i64,
+        "W_PCT" => w_pct: f64,
+        "MIN" => min: f64,
+        "E_OFF_RATING" => e_off_rating: f64,
+        "OFF_RATING" => off_rating: f64,
+        "E_DEF_RATING" => e_def_rating: f64,
+        "DEF_RATING" => def_rating: f64,
+        "E_NET_RATING" => e_net_rating: f64,
+        "NET_RATING" => net_rating: f64,
+        "AST_PCT" => ast_pct: f64,
+        "AST_TO" => ast_to: f64,
+        "AST_RATIO" => ast_ratio: f64,
+        "OREB_PCT" => oreb_pct: f64,
+        "DREB_PCT" => dreb_pct: f64,
+        "REB_PCT" => reb_pct: f64,
+        "TM_TOV_PCT" => tm_tov_pct: f64,
+        "EFG_PCT" => efg_pct: f64,
+        "TS_PCT" => ts_pct: f64,
+        "E_PACE" => e_pace: f64,
+        "PACE" => pace: f64,
+        "PACE_PER40" => pace_per40: f64,
+        "POSS" => poss: i64,
+        "PIE" => pie: f64,
+        "GP_RANK" => gp_rank: i64,
+        "W_RANK" => w_rank: i64,
+        "L_RANK" => l_rank: i64,
+        "W_PCT_RANK" => w_pct_rank: i64,
+        "MIN_RANK" => min_rank: i64,
+        "OFF_RATING_RANK" => off_rating_rank: i64,
+        "DEF_RATING_RANK" => def_rating_rank: i64,
+        "NET_RATING_RANK" => net_rating_rank: i64,
+        "AST_PCT_RANK" => ast_pct_rank: i64,
+        "AST_TO_RANK" => ast_to_rank: i64,
+        "AST_RATIO_RANK" => ast_ratio_rank: i64,
+        "OREB_PCT_RANK" => oreb_pct_rank: i64,
+        "DREB_PCT_RANK" => dreb_pct_rank: i64,
+        "REB_PCT_RANK" => reb_pct_rank: i64,
+        "TM_TOV_PCT_RANK" => tm_tov_pct_rank: i64,
+        "EFG_PCT_RANK" => efg_pct_rank: i64,
+        "TS_PCT_RANK" => ts_pct_rank: i64,
+        "PACE_RANK" => pace_rank: i64,
+        "PIE_RANK" => pie_rank: i64,
+    }
+}
+
+pub async fn fetch_teams_general_advanced(ctx: &FetchContext) -> Result<(), FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::TeamsGeneralAdvanced.url();
+    let endpoint = Endpoint::TeamsGeneralAdvanced.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::TeamsGeneralAdvanced.file_name();
-    let save_file_dir = Endpoint::prepared_data_file_path();
-    let mut save_file_path = save_file_dir.join(file_name);
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
     save_file_path.set_extension("csv");
     // Fetch Data
-    let data: ResultSetsFetchedResponse = fetch_data(endpoint, file_name).await?;
-    let headers = &data.resultSets[0].headers;
-    let rows = &data.resultSets[0].rowSet;
-    write_vector_to_csv(Some(headers.to_vec()), rows.to_vec(), save_file_path).unwrap();
+    let data: ResultSetsFetchedResponse =
+        fetch_data(ctx, Endpoint::TeamsGeneralAdvanced, &endpoint, file_name).await?;
+    write_result_set::<TeamAdvancedRow>(
+        data.first_result_set()?,
+        Endpoint::TeamsGeneralAdvanced,
+        &ctx.query,
+        save_file_path,
+    )?;
     Ok(())
 }
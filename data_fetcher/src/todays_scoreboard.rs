@@ -1,213 +1,226 @@
-#![allow(dead_code)]
-
-use crate::{error::FetchError, fetch_data, Endpoint};
+use crate::{error::FetchError, fetch_data, Endpoint, FetchContext};
 use std::fs::File;
 
 use csv::Writer;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Deserialize, Debug)]
-struct TodaysScoreboardFetchedResponse {
+#[allow(unused)]
+pub struct TodaysScoreboardFetchedResponse {
     meta: Value,
-    scoreboard: TodaysScoreboardData,
+    pub scoreboard: TodaysScoreboardData,
 }
 
 #[derive(Deserialize, Debug)]
-#[allow(non_snake_case)]
-struct TodaysScoreboardData {
-    gameDate: String,
-    leagueId: String,
-    leagueName: String,
-    games: Vec<Value>,
+#[serde(rename_all = "camelCase")]
+pub struct TodaysScoreboardData {
+    pub game_date: String,
+    pub league_id: String,
+    pub league_name: String,
+    pub games: Vec<ScoreboardGame>,
+}
+
+/// `gameStatus` on the live scoreboard.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum GameStatus {
+    Scheduled,
+    InProgress,
+    Final,
+}
+
+impl TryFrom<u8> for GameStatus {
+    type Error = String;
+
+    fn try_from(status: u8) -> Result<Self, Self::Error> {
+        match status {
+            1 => Ok(GameStatus::Scheduled),
+            2 => Ok(GameStatus::InProgress),
+            3 => Ok(GameStatus::Final),
+            other => Err(format!("unknown gameStatus {}", other)),
+        }
+    }
+}
+
+impl From<GameStatus> for u8 {
+    fn from(status: GameStatus) -> Self {
+        match status {
+            GameStatus::Scheduled => 1,
+            GameStatus::InProgress => 2,
+            GameStatus::Final => 3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreboardGame {
+    pub game_id: String,
+    pub game_code: String,
+    pub game_status: GameStatus,
+    pub game_status_text: String,
+    pub period: u32,
+    /// ISO 8601 duration left in the period, e.g. `PT05M32.00S`; empty
+    /// before tip-off.
+    pub game_clock: String,
+    #[serde(rename = "gameTimeUTC")]
+    pub game_time_utc: String,
+    pub home_team: ScoreboardTeam,
+    pub away_team: ScoreboardTeam,
+    pub pb_odds: PbOdds,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreboardTeam {
+    pub team_id: i64,
+    pub team_name: String,
+    pub team_city: String,
+    pub team_tricode: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub score: u32,
+    /// `"1"` once the team is in the penalty, `null` before tip-off.
+    pub in_bonus: Option<String>,
+    pub timeouts_remaining: u32,
+    pub periods: Vec<PeriodScore>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodScore {
+    pub period: u32,
+    pub period_type: String,
+    pub score: u32,
+}
+
+/// The live win-probability odds shown alongside each game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PbOdds {
+    pub team: Option<String>,
+    pub odds: f64,
+    pub suspended: u8,
 }
 
-pub async fn fetch_todays_scoreboard() -> Result<(), FetchError> {
+const HEADERS: [&str; 21] = [
+    "gameId",
+    "gameStatus",
+    "gameStatusText",
+    "period",
+    "gameClock",
+    "gameTimeUTC",
+    "away_teamName",
+    "away_teamCity",
+    "away_teamId",
+    "away_score",
+    "away_periodScores",
+    "away_inBonus",
+    "away_timeoutsRemaining",
+    "home_teamName",
+    "home_teamCity",
+    "home_teamId",
+    "home_score",
+    "home_periodScores",
+    "home_inBonus",
+    "home_timeoutsRemaining",
+    "pbOdds",
+];
+
+impl ScoreboardGame {
+    /// One CSV record in the order of [`HEADERS`]. Period scores are joined
+    /// with `;` so overtimes don't change the column count.
+    fn record(&self) -> Vec<String> {
+        let mut record = vec![
+            self.game_id.clone(),
+            u8::from(self.game_status).to_string(),
+            self.game_status_text.clone(),
+            self.period.to_string(),
+            self.game_clock.clone(),
+            self.game_time_utc.clone(),
+        ];
+        for team in [&self.away_team, &self.home_team] {
+            let period_scores: Vec<String> =
+                team.periods.iter().map(|p| p.score.to_string()).collect();
+            record.extend([
+                team.team_name.clone(),
+                team.team_city.clone(),
+                team.team_id.to_string(),
+                team.score.to_string(),
+                period_scores.join(";"),
+                team.in_bonus.clone().unwrap_or_default(),
+                team.timeouts_remaining.to_string(),
+            ]);
+        }
+        record.push(self.pb_odds.odds.to_string());
+        record
+    }
+}
+
+pub async fn fetch_todays_scoreboard(ctx: &FetchContext) -> Result<(), FetchError> {
+    fetch_scoreboard(ctx).await?;
+    Ok(())
+}
+
+/// Fetches the scoreboard, writes it to `prepared_data` and returns it.
+pub async fn fetch_scoreboard(ctx: &FetchContext) -> Result<TodaysScoreboardData, FetchError> {
     // API Endpoint
-    let endpoint = Endpoint::TodaysScoreboard.url();
+    let endpoint = Endpoint::TodaysScoreboard.url(&ctx.query);
     // File Name for future reference
     let file_name = Endpoint::TodaysScoreboard.file_name();
 
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+
     // Fetch Data
-    let data: TodaysScoreboardFetchedResponse = fetch_data(endpoint, file_name).await?;
+    let data: TodaysScoreboardFetchedResponse =
+        fetch_data(ctx, Endpoint::TodaysScoreboard, &endpoint, file_name).await?;
 
-    let file = File::create("")?;
+    let file = File::create(save_file_path)?;
     let mut wtr = Writer::from_writer(file);
-
-    let scoreboard = &data.scoreboard;
-    let games = &scoreboard.games;
-
-    let headers = vec![
-        "away_teamName",
-        "away_teamCity",
-        "away_teamId",
-        "away_score",
-        "home_teamName",
-        "home_teamCity",
-        "home_teamId",
-        "home_score",
-    ];
-
-    wtr.write_record(headers)?;
-
-    for game in games {
-        let away_team = game.get("awayTeam").unwrap();
-        let away_team_name = away_team
-            .get("teamName")
-            .unwrap()
-            .clone()
-            .as_str()
-            .unwrap()
-            .to_string();
-        let away_team_city = away_team
-            .get("teamCity")
-            .unwrap()
-            .clone()
-            .as_str()
-            .unwrap()
-            .to_string();
-        let away_team_id = away_team.get("teamId").unwrap().to_string();
-        let away_team_score = away_team.get("score").unwrap().to_string();
-
-        let home_team = game.get("homeTeam").unwrap();
-        let home_team_name = home_team
-            .get("teamName")
-            .unwrap()
-            .clone()
-            .as_str()
-            .unwrap()
-            .to_string();
-        let home_team_city = home_team
-            .get("teamCity")
-            .unwrap()
-            .clone()
-            .as_str()
-            .unwrap()
-            .to_string();
-        let home_team_id = home_team.get("teamId").unwrap().to_string();
-        let home_team_score = home_team.get("score").unwrap().to_string();
-
-        let game_record = vec![
-            away_team_name,
-            away_team_city,
-            away_team_id,
-            away_team_score,
-            home_team_name,
-            home_team_city,
-            home_team_id,
-            home_team_score,
-        ];
-
-        wtr.write_record(game_record)?;
+    wtr.write_record(HEADERS)?;
+    for game in &data.scoreboard.games {
+        wtr.write_record(game.record())?;
     }
-
     wtr.flush()?;
 
-    Ok(())
+    Ok(data.scoreboard)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{env, fs, path::Path};
+    use std::fs;
 
     use super::*;
 
     #[test]
-    fn feature() {
-        let file_name = Endpoint::TeamsGeneralOpponent.file_name();
-        let cwd = env::current_dir().unwrap();
-        let file_path = Path::new(&cwd).join("data").join("prepared_data");
-        let save_file_dir = Endpoint::prepared_data_file_path();
-        let mut save_file_path = file_path.join(file_name);
-        save_file_path.set_extension("csv");
-        
-        let cwd = env::current_dir().unwrap();
-        let mut file_path = Path::new(&cwd).join("data").join("prepared_data");
-        println!("{:#?}", file_path)
-    }
-
-    #[test]
-    fn test_name() {
+    fn seed_scoreboard_parses_into_typed_games() {
         let file = fs::File::open("../data/seed_data/todaysScoreboard_00.json")
             .expect("file should open read only");
         let json: TodaysScoreboardFetchedResponse =
             serde_json::from_reader(file).expect("file should be proper JSON");
-        let scoreboard = &json.scoreboard;
-        let games = &scoreboard.games;
-
-        let mut csv_record_collection: Vec<Vec<String>> = vec![];
-
-        for game in games {
-            let away_team = game.get("awayTeam").unwrap();
-            let away_team_name = away_team
-                .get("teamName")
-                .unwrap()
-                .clone()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let away_team_city = away_team
-                .get("teamCity")
-                .unwrap()
-                .clone()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let away_team_id = away_team
-                .get("teamId")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let away_team_score = away_team
-                .get("score")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-
-            let home_team = game.get("homeTeam").unwrap();
-            let home_team_name = home_team
-                .get("teamName")
-                .unwrap()
-                .clone()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let home_team_city = home_team
-                .get("teamCity")
-                .unwrap()
-                .clone()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let home_team_id = home_team
-                .get("teamId")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let home_team_score = home_team
-                .get("score")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-
-            let game_record = vec![
-                away_team_name,
-                away_team_city,
-                away_team_id,
-                away_team_score,
-                home_team_name,
-                home_team_city,
-                home_team_id,
-                home_team_score,
-            ];
-
-            csv_record_collection.push(game_record);
-        }
+        let games = &json.scoreboard.games;
+
+        assert_eq!(games.len(), 3);
+        let game = &games[0];
+        assert_eq!(game.game_id, "0022400181");
+        assert_eq!(game.game_status, GameStatus::Scheduled);
+        assert_eq!(game.home_team.team_tricode, "CHI");
+        assert_eq!(game.away_team.periods.len(), 4);
+        assert_eq!(game.home_team.in_bonus, None);
+
+        let record = game.record();
+        assert_eq!(record.len(), HEADERS.len());
+        assert_eq!(record[6], "Timberwolves");
+        assert_eq!(record[10], "0;0;0;0");
+    }
 
-        println!("{:#?}", csv_record_collection)
+    #[test]
+    fn unknown_game_status_is_rejected() {
+        assert!(serde_json::from_str::<GameStatus>("4").is_err());
+        assert_eq!(
+            serde_json::from_str::<GameStatus>("2").unwrap(),
+            GameStatus::InProgress
+        );
     }
 }
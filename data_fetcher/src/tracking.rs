@@ -0,0 +1,451 @@
+use crate::{
+    error::FetchError,
+    fetch_data,
+    query::PtMeasureType,
+    result_sets::{
+        missing_columns, result_set_row, write_rows_to_csv, ResultSetRow, ResultSetsData,
+        ResultSetsFetchedResponse,
+    },
+    Endpoint, FetchContext,
+};
+use csv::WriterBuilder;
+use serde_json::Value;
+use std::{fs::File, path::PathBuf};
+
+result_set_row! {
+    /// One player from `leaguehustlestatsplayer`.
+    pub struct PlayerHustleRow {
+        "PLAYER_ID" => player_id: i64,
+        "PLAYER_NAME" => player_name: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "AGE" => age: Option<f64>,
+        "G" => g: i64,
+        "MIN" => min: f64,
+        "CONTESTED_SHOTS" => contested_shots: f64,
+        "CONTESTED_SHOTS_2PT" => contested_shots_2pt: f64,
+        "CONTESTED_SHOTS_3PT" => contested_shots_3pt: f64,
+        "DEFLECTIONS" => deflections: f64,
+        "CHARGES_DRAWN" => charges_drawn: f64,
+        "SCREEN_ASSISTS" => screen_assists: f64,
+        "SCREEN_AST_PTS" => screen_ast_pts: f64,
+        "OFF_LOOSE_BALLS_RECOVERED" => off_loose_balls_recovered: f64,
+        "DEF_LOOSE_BALLS_RECOVERED" => def_loose_balls_recovered: f64,
+        "LOOSE_BALLS_RECOVERED" => loose_balls_recovered: f64,
+        "PCT_LOOSE_BALLS_RECOVERED_OFF" => pct_loose_balls_recovered_off: Option<f64>,
+        "PCT_LOOSE_BALLS_RECOVERED_DEF" => pct_loose_balls_recovered_def: Option<f64>,
+        "OFF_BOXOUTS" => off_boxouts: f64,
+        "DEF_BOXOUTS" => def_boxouts: f64,
+        "BOX_OUT_PLAYER_TEAM_REBS" => box_out_player_team_rebs: f64,
+        "BOX_OUT_PLAYER_REBS" => box_out_player_rebs: f64,
+        "BOX_OUTS" => box_outs: f64,
+        "PCT_BOX_OUTS_OFF" => pct_box_outs_off: Option<f64>,
+        "PCT_BOX_OUTS_DEF" => pct_box_outs_def: Option<f64>,
+        "PCT_BOX_OUTS_TEAM_REB" => pct_box_outs_team_reb: Option<f64>,
+        "PCT_BOX_OUTS_REB" => pct_box_outs_reb: Option<f64>,
+    }
+}
+
+result_set_row! {
+    /// One team from `leaguehustlestatsteam`.
+    pub struct TeamHustleRow {
+        "TEAM_ID" => team_id: i64,
+        "TEAM_NAME" => team_name: String,
+        "MIN" => min: f64,
+        "CONTESTED_SHOTS" => contested_shots: f64,
+        "CONTESTED_SHOTS_2PT" => contested_shots_2pt: f64,
+        "CONTESTED_SHOTS_3PT" => contested_shots_3pt: f64,
+        "DEFLECTIONS" => deflections: f64,
+        "CHARGES_DRAWN" => charges_drawn: f64,
+        "SCREEN_ASSISTS" => screen_assists: f64,
+        "SCREEN_AST_PTS" => screen_ast_pts: f64,
+        "OFF_LOOSE_BALLS_RECOVERED" => off_loose_balls_recovered: f64,
+        "DEF_LOOSE_BALLS_RECOVERED" => def_loose_balls_recovered: f64,
+        "LOOSE_BALLS_RECOVERED" => loose_balls_recovered: f64,
+        "PCT_LOOSE_BALLS_RECOVERED_OFF" => pct_loose_balls_recovered_off: Option<f64>,
+        "PCT_LOOSE_BALLS_RECOVERED_DEF" => pct_loose_balls_recovered_def: Option<f64>,
+        "OFF_BOXOUTS" => off_boxouts: f64,
+        "DEF_BOXOUTS" => def_boxouts: f64,
+        "BOX_OUTS" => box_outs: f64,
+        "PCT_BOX_OUTS_OFF" => pct_box_outs_off: Option<f64>,
+        "PCT_BOX_OUTS_DEF" => pct_box_outs_def: Option<f64>,
+    }
+}
+
+result_set_row! {
+    /// The columns every player measure of `leaguedashptstats` starts with.
+    pub struct PlayerTrackingRow {
+        "PLAYER_ID" => player_id: i64,
+        "PLAYER_NAME" => player_name: String,
+        "TEAM_ID" => team_id: i64,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "GP" => gp: i64,
+        "W" => w: i64,
+        "L" => l: i64,
+        "MIN" => min: f64,
+    }
+}
+
+result_set_row! {
+    /// The columns every team measure of `leaguedashptstats` starts with.
+    pub struct TeamTrackingRow {
+        "TEAM_ID" => team_id: i64,
+        "TEAM_NAME" => team_name: String,
+        "TEAM_ABBREVIATION" => team_abbreviation: String,
+        "GP" => gp: i64,
+        "W" => w: i64,
+        "L" => l: i64,
+        "MIN" => min: f64,
+    }
+}
+
+/// The statistics one tracking measure reports after its identifying
+/// columns, the same for players and teams.
+pub fn tracking_columns(measure: PtMeasureType) -> &'static [&'static str] {
+    match measure {
+        PtMeasureType::SpeedDistance => &[
+            "DIST_FEET",
+            "DIST_MILES",
+            "DIST_MILES_OFF",
+            "DIST_MILES_DEF",
+            "AVG_SPEED",
+            "AVG_SPEED_OFF",
+            "AVG_SPEED_DEF",
+        ],
+        PtMeasureType::Possessions => &[
+            "POINTS",
+            "TOUCHES",
+            "FRONT_CT_TOUCHES",
+            "TIME_OF_POSS",
+            "AVG_SEC_PER_TOUCH",
+            "AVG_DRIB_PER_TOUCH",
+            "PTS_PER_TOUCH",
+            "ELBOW_TOUCHES",
+            "POST_TOUCHES",
+            "PAINT_TOUCHES",
+            "PTS_PER_ELBOW_TOUCH",
+            "PTS_PER_POST_TOUCH",
+            "PTS_PER_PAINT_TOUCH",
+        ],
+        PtMeasureType::Drives => &[
+            "DRIVES",
+            "DRIVE_FGM",
+            "DRIVE_FGA",
+            "DRIVE_FG_PCT",
+            "DRIVE_FTM",
+            "DRIVE_FTA",
+            "DRIVE_FT_PCT",
+            "DRIVE_PTS",
+            "DRIVE_PTS_PCT",
+            "DRIVE_PASSES",
+            "DRIVE_PASSES_PCT",
+            "DRIVE_AST",
+            "DRIVE_AST_PCT",
+            "DRIVE_TOV",
+            "DRIVE_TOV_PCT",
+            "DRIVE_PF",
+            "DRIVE_PF_PCT",
+        ],
+        PtMeasureType::CatchShoot => &[
+            "CATCH_SHOOT_FGM",
+            "CATCH_SHOOT_FGA",
+            "CATCH_SHOOT_FG_PCT",
+            "CATCH_SHOOT_PTS",
+            "CATCH_SHOOT_FG3M",
+            "CATCH_SHOOT_FG3A",
+            "CATCH_SHOOT_FG3_PCT",
+            "CATCH_SHOOT_EFG_PCT",
+        ],
+        PtMeasureType::PullUpShot => &[
+            "PULL_UP_FGM",
+            "PULL_UP_FGA",
+            "PULL_UP_FG_PCT",
+            "PULL_UP_PTS",
+            "PULL_UP_FG3M",
+            "PULL_UP_FG3A",
+            "PULL_UP_FG3_PCT",
+            "PULL_UP_EFG_PCT",
+        ],
+        PtMeasureType::Rebounding => &[
+            "OREB",
+            "OREB_CONTEST",
+            "OREB_UNCONTEST",
+            "OREB_CONTEST_PCT",
+            "OREB_CHANCES",
+            "OREB_CHANCE_PCT",
+            "OREB_CHANCE_DEFER",
+            "OREB_CHANCE_PCT_ADJ",
+            "AVG_OREB_DIST",
+            "DREB",
+            "DREB_CONTEST",
+            "DREB_UNCONTEST",
+            "DREB_CONTEST_PCT",
+            "DREB_CHANCES",
+            "DREB_CHANCE_PCT",
+            "DREB_CHANCE_DEFER",
+            "DREB_CHANCE_PCT_ADJ",
+            "AVG_DREB_DIST",
+            "REB",
+            "REB_CONTEST",
+            "REB_UNCONTEST",
+            "REB_CONTEST_PCT",
+            "REB_CHANCES",
+            "REB_CHANCE_PCT",
+            "REB_CHANCE_DEFER",
+            "REB_CHANCE_PCT_ADJ",
+            "AVG_REB_DIST",
+        ],
+        PtMeasureType::Defense => &[
+            "STL",
+            "BLK",
+            "DREB",
+            "DEF_RIM_FGM",
+            "DEF_RIM_FGA",
+            "DEF_RIM_FG_PCT",
+        ],
+    }
+}
+
+const HUSTLE_PLAYER: &str = "HustleStatsPlayer";
+const HUSTLE_TEAM: &str = "HustleStatsTeam";
+
+/// Fetches one `leaguedashptstats` measure. Each measure has its own
+/// statistics after the identifying columns, so those are typed by
+/// [`PlayerTrackingRow`] or [`TeamTrackingRow`] and the measure's
+/// [`tracking_columns`] are read as numbers, empty when not sent.
+pub async fn fetch_tracking(endpoint: Endpoint, ctx: &FetchContext) -> Result<(), FetchError> {
+    let measure = match endpoint {
+        Endpoint::PlayerTracking(measure) | Endpoint::TeamTracking(measure) => measure,
+        other => return Err(FetchError::NotATrackingMeasure(other)),
+    };
+    // API Endpoint
+    let url = endpoint.url(&ctx.query);
+    // File Name for future reference
+    let file_name = endpoint.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+    // Fetch Data
+    let data: ResultSetsFetchedResponse = fetch_data(ctx, endpoint, &url, file_name).await?;
+    let set = data.first_result_set()?;
+    let columns = tracking_columns(measure);
+    match endpoint {
+        Endpoint::PlayerTracking(_) => {
+            let rows: Vec<PlayerTrackingRow> = set.rows()?;
+            write_tracking_rows(endpoint, set, &rows, columns, save_file_path)
+        }
+        _ => {
+            let rows: Vec<TeamTrackingRow> = set.rows()?;
+            write_tracking_rows(endpoint, set, &rows, columns, save_file_path)
+        }
+    }
+}
+
+/// Writes each identifying row followed by its `columns`, in that order.
+fn write_tracking_rows<T: ResultSetRow>(
+    endpoint: Endpoint,
+    set: &ResultSetsData,
+    rows: &[T],
+    columns: &[&str],
+    path: PathBuf,
+) -> Result<(), FetchError> {
+    let missing = missing_columns(&set.headers, columns);
+    if !missing.is_empty() {
+        return Err(FetchError::MissingColumns {
+            result_set: set.name.clone(),
+            missing,
+        });
+    }
+    let indices: Vec<usize> = columns
+        .iter()
+        .map(|c| set.headers.iter().position(|h| h == c).unwrap())
+        .collect();
+
+    let mut wtr = WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(File::create(path)?);
+    wtr.write_record(T::COLUMNS.iter().chain(columns))?;
+    for (row, sent) in rows.iter().zip(&set.rowSet) {
+        let stats = indices
+            .iter()
+            .zip(columns)
+            .map(|(&i, column)| match sent.get(i) {
+                None | Some(Value::Null) => Ok(None),
+                Some(value) => {
+                    value
+                        .as_f64()
+                        .map(Some)
+                        .ok_or_else(|| FetchError::UnexpectedShape {
+                            endpoint,
+                            pointer: format!("{}/{}", set.name, column),
+                        })
+                }
+            })
+            .collect::<Result<Vec<Option<f64>>, FetchError>>()?;
+        wtr.serialize((row, stats))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub async fn fetch_hustle(endpoint: Endpoint, ctx: &FetchContext) -> Result<(), FetchError> {
+    if !matches!(endpoint, Endpoint::PlayerHustle | Endpoint::TeamHustle) {
+        return Err(FetchError::NotAHustleEndpoint(endpoint));
+    }
+    // API Endpoint
+    let url = endpoint.url(&ctx.query);
+    // File Name for future reference
+    let file_name = endpoint.file_name();
+    let mut save_file_path = ctx.dirs.prepared.join(file_name);
+    save_file_path.set_extension("csv");
+    // Fetch Data
+    let data: ResultSetsFetchedResponse = fetch_data(ctx, endpoint, &url, file_name).await?;
+    match endpoint {
+        Endpoint::PlayerHustle => {
+            let rows: Vec<PlayerHustleRow> = data.result_set(HUSTLE_PLAYER)?.rows()?;
+            write_rows_to_csv(&rows, save_file_path)
+        }
+        Endpoint::TeamHustle => {
+            let rows: Vec<TeamHustleRow> = data.result_set(HUSTLE_TEAM)?.rows()?;
+            write_rows_to_csv(&rows, save_file_path)
+        }
+        other => Err(FetchError::NotAHustleEndpoint(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        query::{PerMode, PtMeasureType, StatsQuery},
+        transport::FixtureTransport,
+        OutputDirs,
+    };
+    use serde_json::json;
+    use std::{env, fs, sync::Arc};
+
+    #[test]
+    fn tracking_urls_name_the_measure_and_per_mode() {
+        let query = StatsQuery::new().with_per_mode(PerMode::Totals);
+        let url = Endpoint::TeamTracking(PtMeasureType::CatchShoot).url(&query);
+        assert!(url.contains("PtMeasureType=CatchShoot"));
+        assert!(url.contains("PlayerOrTeam=Team"));
+        assert!(url.contains("PerMode=Totals"));
+    }
+
+    #[tokio::test]
+    async fn tracking_and_hustle_are_written_per_endpoint() {
+        let dir = env::temp_dir().join("nba_tracking");
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+
+        for measure in PtMeasureType::ALL {
+            fetch_tracking(Endpoint::PlayerTracking(measure), &ctx)
+                .await
+                .unwrap();
+            fetch_tracking(Endpoint::TeamTracking(measure), &ctx)
+                .await
+                .unwrap();
+        }
+        fetch_hustle(Endpoint::PlayerHustle, &ctx).await.unwrap();
+        fetch_hustle(Endpoint::TeamHustle, &ctx).await.unwrap();
+        assert!(matches!(
+            fetch_hustle(Endpoint::PlayerTracking(PtMeasureType::Drives), &ctx).await,
+            Err(FetchError::NotAHustleEndpoint(_))
+        ));
+
+        let mut drives =
+            csv::Reader::from_path(dirs.prepared.join("fetched_PlayerTrackingDrives_data.csv"))
+                .unwrap();
+        let headers = drives.headers().unwrap().clone();
+        let expected: Vec<&str> = PlayerTrackingRow::COLUMNS
+            .iter()
+            .chain(tracking_columns(PtMeasureType::Drives))
+            .copied()
+            .collect();
+        assert_eq!(headers, expected);
+        let column = headers.iter().position(|h| h == "DRIVES").unwrap();
+        let cade = drives.records().next().unwrap().unwrap();
+        assert_eq!(&cade[1], "Cade Cunningham");
+        assert_eq!(&cade[column], "17.6");
+
+        let rim =
+            fs::read_to_string(dirs.prepared.join("fetched_TeamTrackingDefense_data.csv")).unwrap();
+        assert!(rim.lines().next().unwrap().ends_with("DEF_RIM_FG_PCT"));
+
+        let mut hustle =
+            csv::Reader::from_path(dirs.prepared.join("fetched_PlayerHustle_data.csv")).unwrap();
+        let rows: Vec<PlayerHustleRow> = hustle.deserialize().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[1].player_name, "Jalen Duren");
+        assert_eq!(rows[1].screen_assists, 3.1);
+
+        let mut teams =
+            csv::Reader::from_path(dirs.prepared.join("fetched_TeamHustle_data.csv")).unwrap();
+        let rows: Vec<TeamHustleRow> = teams.deserialize().map(Result::unwrap).collect();
+        assert_eq!(rows[0].deflections, 16.2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tracking_values_are_typed() {
+        let dir = env::temp_dir().join("nba_tracking_typed");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("defense.csv");
+        let endpoint = Endpoint::TeamTracking(PtMeasureType::Defense);
+        let mut headers: Vec<String> = TeamTrackingRow::COLUMNS
+            .iter()
+            .chain(tracking_columns(PtMeasureType::Defense))
+            .map(|c| c.to_string())
+            .collect();
+        headers.push("NEW_STAT".to_string());
+        let identity = [
+            json!(1610612765),
+            json!("Detroit Pistons"),
+            json!("DET"),
+            json!(11),
+            json!(4),
+            json!(7),
+            json!(48.0),
+        ];
+        let row = |rim_pct: Value| {
+            let mut row = identity.to_vec();
+            row.extend([
+                json!(11.4),
+                json!(37.3),
+                json!(37.4),
+                json!(63.4),
+                json!(14.5),
+            ]);
+            row.extend([rim_pct, json!(1)]);
+            row
+        };
+        let set = |rows| ResultSetsData {
+            name: "LeagueDashPtStats".to_string(),
+            headers: headers.clone(),
+            rowSet: rows,
+        };
+
+        // Unknown columns are dropped and a null is left empty.
+        let sent = set(vec![row(Value::Null)]);
+        let rows: Vec<TeamTrackingRow> = sent.rows().unwrap();
+        let columns = tracking_columns(PtMeasureType::Defense);
+        write_tracking_rows(endpoint, &sent, &rows, columns, path.clone()).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        let mut lines = written.lines();
+        assert!(lines.next().unwrap().ends_with(",DEF_RIM_FG_PCT"));
+        assert!(lines.next().unwrap().ends_with(",14.5,"));
+
+        let sent = set(vec![row(json!("n/a"))]);
+        let error = write_tracking_rows(endpoint, &sent, &rows, columns, path).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("LeagueDashPtStats/DEF_RIM_FG_PCT"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    cache::CacheStatus,
+    client::{shared_client, HttpClient},
+    error::FetchError,
+    market_odds::{OddsMarket, OddsScope},
+    query::PtMeasureType,
+    Endpoint,
+};
+use reqwest::{StatusCode, Url};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response, FetchError>> + Send + 'a>>;
+
+/// A response body and the HTTP status it was sent with. `status` is `None`
+/// when no request was made, e.g. for a fixture or a fresh cache entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: Option<StatusCode>,
+    pub body: Vec<u8>,
+}
+
+/// Where response bodies come from. `fetch_data` asks its transport for the
+/// bytes behind `url` and does the decoding and saving itself, so swapping
+/// the transport changes nothing downstream.
+pub trait Transport: Send + Sync + fmt::Debug {
+    fn get<'a>(&'a self, endpoint: Endpoint, url: &'a str) -> TransportFuture<'a>;
+
+    /// Whether the last response for `endpoint` came from a cache, for
+    /// transports that keep one.
+    fn take_cache_status(&self, _endpoint: Endpoint) -> Option<CacheStatus> {
+        None
+    }
+}
+
+/// Goes to the network through the shared, rate-limited client.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HttpTransport;
+
+impl Transport for HttpTransport {
+    fn get<'a>(&'a self, _endpoint: Endpoint, url: &'a str) -> TransportFuture<'a> {
+        Box::pin(shared_client().get_response(url))
+    }
+}
+
+pub fn http_transport() -> Arc<dyn Transport> {
+    Arc::new(HttpTransport)
+}
+
+/// Sends every request to `origin` instead of the host in the URL, keeping
+/// the path and query, e.g. to go through a mirror or a local stand-in.
+#[derive(Debug)]
+pub struct RedirectTransport {
+    origin: Url,
+    client: HttpClient,
+}
+
+impl RedirectTransport {
+    pub fn new(origin: &str, client: HttpClient) -> Self {
+        RedirectTransport {
+            origin: Url::parse(origin).expect("origin is a valid URL"),
+            client,
+        }
+    }
+
+    pub fn redirect(&self, url: &str) -> String {
+        match Url::parse(url) {
+            Ok(url) => {
+                let mut redirected = self.origin.join(url.path()).expect("path joins origin");
+                redirected.set_query(url.query());
+                redirected.to_string()
+            }
+            Err(_) => url.to_string(),
+        }
+    }
+}
+
+impl Transport for RedirectTransport {
+    fn get<'a>(&'a self, _endpoint: Endpoint, url: &'a str) -> TransportFuture<'a> {
+        let url = self.redirect(url);
+        Box::pin(async move { self.client.get_response(&url).await })
+    }
+}
+
+/// Serves saved responses from a directory instead of the network, one file
+/// per `Endpoint`, so the pipeline runs offline and deterministically.
+#[derive(Debug, Clone)]
+pub struct FixtureTransport {
+    dir: PathBuf,
+    overrides: HashMap<Endpoint, PathBuf>,
+}
+
+impl FixtureTransport {
+    /// Serves `dir`, expecting files named as in [`fixture_file_name`].
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FixtureTransport {
+            dir: dir.into(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// The responses checked in under `../data/seed_data`.
+    pub fn seed_data() -> Self {
+        Self::new(Endpoint::data_file_path().join("seed_data"))
+    }
+
+    /// Serves `path` for `endpoint` instead of the file in the directory.
+    pub fn with_file(mut self, endpoint: Endpoint, path: impl Into<PathBuf>) -> Self {
+        self.overrides.insert(endpoint, path.into());
+        self
+    }
+
+    pub fn path_for(&self, endpoint: Endpoint) -> PathBuf {
+        self.overrides
+            .get(&endpoint)
+            .cloned()
+            .unwrap_or_else(|| self.dir.join(fixture_file_name(endpoint)))
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn get<'a>(&'a self, endpoint: Endpoint, _url: &'a str) -> TransportFuture<'a> {
+        let path = self.path_for(endpoint);
+        Box::pin(async move {
+            Ok(Response {
+                status: None,
+                body: read_fixture(&path)?,
+            })
+        })
+    }
+}
+
+fn read_fixture(path: &Path) -> Result<Vec<u8>, FetchError> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(bytes),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            Err(FetchError::MissingFixture(path.to_path_buf()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// File name a fixture directory uses for each endpoint. These match the
+/// names stats.nba.com and the other sources give their responses, which is
+/// how `data/seed_data` is laid out.
+pub fn fixture_file_name(endpoint: Endpoint) -> &'static str {
+    match endpoint {
+        Endpoint::SeasonSchedule => "scheduleLeagueV2_51.json",
+        Endpoint::TodaysScoreboard => "todaysScoreboard_00.json",
+        Endpoint::PlayerIndex => "playerindex.json",
+        Endpoint::PlayerGeneralAverages => "leaguedashplayerstats.json",
+        Endpoint::TeamsGeneralAdvanced => "leaguedashteamstatsAdvanced.json",
+        Endpoint::OpponentShootingGeneral => "leaguedashoppptshot.json",
+        Endpoint::TeamsGeneralOpponent => "leaguedashteamstats.json",
+        Endpoint::PlayerGameLogs => "leaguegamelogPlayer.json",
+        Endpoint::TeamGameLogs => "leaguegamelogTeam.json",
+        Endpoint::Lineups => "leaguedashlineups.json",
+        Endpoint::PlayerOnOff => "teamplayeronoffsummary.json",
+        Endpoint::Standings => "leaguestandingsv3.json",
+        Endpoint::PlayerTracking(PtMeasureType::SpeedDistance) => {
+            "leaguedashptstatsPlayerSpeedDistance.json"
+        }
+        Endpoint::PlayerTracking(PtMeasureType::Possessions) => {
+            "leaguedashptstatsPlayerPossessions.json"
+        }
+        Endpoint::PlayerTracking(PtMeasureType::Drives) => "leaguedashptstatsPlayerDrives.json",
+        Endpoint::PlayerTracking(PtMeasureType::CatchShoot) => {
+            "leaguedashptstatsPlayerCatchShoot.json"
+        }
+        Endpoint::PlayerTracking(PtMeasureType::PullUpShot) => {
+            "leaguedashptstatsPlayerPullUpShot.json"
+        }
+        Endpoint::PlayerTracking(PtMeasureType::Rebounding) => {
+            "leaguedashptstatsPlayerRebounding.json"
+        }
+        Endpoint::PlayerTracking(PtMeasureType::Defense) => "leaguedashptstatsPlayerDefense.json",
+        Endpoint::TeamTracking(PtMeasureType::SpeedDistance) => {
+            "leaguedashptstatsTeamSpeedDistance.json"
+        }
+        Endpoint::TeamTracking(PtMeasureType::Possessions) => {
+            "leaguedashptstatsTeamPossessions.json"
+        }
+        Endpoint::TeamTracking(PtMeasureType::Drives) => "leaguedashptstatsTeamDrives.json",
+        Endpoint::TeamTracking(PtMeasureType::CatchShoot) => "leaguedashptstatsTeamCatchShoot.json",
+        Endpoint::TeamTracking(PtMeasureType::PullUpShot) => "leaguedashptstatsTeamPullUpShot.json",
+        Endpoint::TeamTracking(PtMeasureType::Rebounding) => "leaguedashptstatsTeamRebounding.json",
+        Endpoint::TeamTracking(PtMeasureType::Defense) => "leaguedashptstatsTeamDefense.json",
+        Endpoint::PlayerHustle => "leaguehustlestatsplayer.json",
+        Endpoint::TeamHustle => "leaguehustlestatsteam.json",
+        Endpoint::ShotChart => "shotchartdetail.json",
+        Endpoint::InjuryReport => "injuryReport.json",
+        Endpoint::Bet365Odds => "moneyLineFullGame.json",
+        Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FullGame) => "moneyLineFullGame.json",
+        Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstHalf) => "moneyLineFirstHalf.json",
+        Endpoint::Odds(OddsMarket::MoneyLine, OddsScope::FirstQuarter) => {
+            "moneyLineFirstQuarter.json"
+        }
+        Endpoint::Odds(OddsMarket::Spread, OddsScope::FullGame) => "pointSpreadFullGame.json",
+        Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstHalf) => "pointSpreadFirstHalf.json",
+        Endpoint::Odds(OddsMarket::Spread, OddsScope::FirstQuarter) => {
+            "pointSpreadFirstQuarter.json"
+        }
+        Endpoint::Odds(OddsMarket::Total, OddsScope::FullGame) => "totalsFullGame.json",
+        Endpoint::Odds(OddsMarket::Total, OddsScope::FirstHalf) => "totalsFirstHalf.json",
+        Endpoint::Odds(OddsMarket::Total, OddsScope::FirstQuarter) => "totalsFirstQuarter.json",
+        Endpoint::OddsLandingPage => "bettingOddsNbaBasketball.html",
+        Endpoint::BoxScoreTraditional => "boxscoretraditionalv3.json",
+        Endpoint::BoxScoreAdvanced => "boxscoreadvancedv3.json",
+        Endpoint::BoxScoreFourFactors => "boxscorefourfactorsv3.json",
+        Endpoint::BoxScoreUsage => "boxscoreusagev3.json",
+        Endpoint::PlayByPlay => "playbyplay.json",
+    }
+}
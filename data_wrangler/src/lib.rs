@@ -1,2 +1,10 @@
 pub mod player_stats;
+pub mod tracking;
 
+use std::{env, path::PathBuf};
+
+/// `../data`, the directory `data_fetcher` writes into.
+pub fn data_file_path() -> PathBuf {
+    let parent = env::current_dir().unwrap();
+    parent.parent().unwrap().join("data")
+}
@@ -1,8 +1,11 @@
+use crate::data_file_path;
 use deunicode::deunicode;
 use polars::prelude::*;
 
 pub fn read_player_index() -> DataFrame {
-    let path = r"C:\Users\Danie\Desktop\programming\rust\nba_prediction_engine\data\prepared_data\fetched_PlayerIndex_data.csv";
+    let path = data_file_path()
+        .join("prepared_data")
+        .join("fetched_PlayerIndex_data.csv");
 
     // Load the CSV as a LazyFrame
     let player_index_df = LazyCsvReader::new(path)
@@ -66,12 +69,14 @@ fn normalize_name(player_name: &Column) -> Column {
 }
 
 pub fn read_player_averages() -> DataFrame {
-    let path = r"C:\Users\Danie\Desktop\programming\rust\nba_prediction_engine\data\prepared_data\fetched_PlayerGeneralAverages_data.csv";
+    let path = data_file_path()
+        .join("prepared_data")
+        .join("fetched_PlayerGeneralAverages_data.csv");
 
     let player_averages_df = CsvReadOptions::default()
         .with_infer_schema_length(None)
         .with_has_header(true)
-        .try_into_reader_with_file_path(Some(path.into()))
+        .try_into_reader_with_file_path(Some(path))
         .unwrap()
         .finish()
         .unwrap();
@@ -126,7 +131,9 @@ pub fn read_player_averages() -> DataFrame {
 }
 
 pub fn read_player_injury_report() -> DataFrame {
-    let path = r"C:\Users\Danie\Desktop\programming\rust\nba_prediction_engine\data\fetched_data_output_as_string\fetched_InjuryReport_data";
+    let path = data_file_path()
+        .join("fetched_data_output_as_string")
+        .join("fetched_InjuryReport_data.txt");
 
     let mut file = std::fs::File::open(path).unwrap();
     let player_injury_report_df = JsonReader::new(&mut file).finish().unwrap();
@@ -158,7 +165,7 @@ pub fn player_stats_df() {
         .left_join(&player_injury_report_df, ["PLAYER_NAME"], ["player"])
         .unwrap();
 
-    let path = r"C:\Users\Danie\Desktop\programming\rust\nba_prediction_engine\data\wrangled_data";
+    let path = data_file_path().join("wrangled_data");
     let mut file = std::fs::File::create(path).unwrap();
     CsvWriter::new(&mut file)
         .finish(&mut player_index_averages_injury_report_df)
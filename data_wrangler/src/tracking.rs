@@ -0,0 +1,188 @@
+use crate::data_file_path;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The `leaguedashptstats` measures `data_fetcher` writes, one file each.
+pub const TRACKING_MEASURES: [&str; 7] = [
+    "SpeedDistance",
+    "Possessions",
+    "Drives",
+    "CatchShoot",
+    "PullUpShot",
+    "Rebounding",
+    "Defense",
+];
+
+/// Columns every player measure starts with; only `PLAYER_ID` is kept when
+/// measures are joined.
+const PLAYER_TRACKING_IDENTITY: [&str; 7] = [
+    "PLAYER_NAME",
+    "TEAM_ID",
+    "TEAM_ABBREVIATION",
+    "GP",
+    "W",
+    "L",
+    "MIN",
+];
+
+/// Columns every team measure starts with; only `TEAM_ID` is kept when
+/// measures are joined.
+const TEAM_TRACKING_IDENTITY: [&str; 6] = ["TEAM_NAME", "TEAM_ABBREVIATION", "GP", "W", "L", "MIN"];
+
+/// `prepared_data`, where `data_fetcher` writes the tracking and hustle
+/// files.
+pub fn prepared_data_path() -> PathBuf {
+    data_file_path().join("prepared_data")
+}
+
+fn read_prepared_csv(prepared: &Path, file_name: &str) -> PolarsResult<DataFrame> {
+    CsvReadOptions::default()
+        .with_infer_schema_length(None)
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(prepared.join(file_name)))?
+        .finish()
+}
+
+/// One player tracking measure, e.g. `Drives`.
+pub fn read_player_tracking(prepared: &Path, measure: &str) -> PolarsResult<DataFrame> {
+    read_prepared_csv(
+        prepared,
+        &format!("fetched_PlayerTracking{}_data.csv", measure),
+    )
+}
+
+/// One team tracking measure, e.g. `Drives`.
+pub fn read_team_tracking(prepared: &Path, measure: &str) -> PolarsResult<DataFrame> {
+    read_prepared_csv(
+        prepared,
+        &format!("fetched_TeamTracking{}_data.csv", measure),
+    )
+}
+
+pub fn read_player_hustle(prepared: &Path) -> PolarsResult<DataFrame> {
+    read_prepared_csv(prepared, "fetched_PlayerHustle_data.csv")
+}
+
+pub fn read_team_hustle(prepared: &Path) -> PolarsResult<DataFrame> {
+    read_prepared_csv(prepared, "fetched_TeamHustle_data.csv")
+}
+
+/// Left-joins `frames` on `key`, in order. A column already taken from an
+/// earlier frame, e.g. `DREB` in both Rebounding and Defense, is not joined
+/// again.
+fn join_on(frames: Vec<DataFrame>, key: &str) -> PolarsResult<DataFrame> {
+    let mut frames = frames.into_iter();
+    let mut joined = frames
+        .next()
+        .ok_or_else(|| polars_err!(NoData: "nothing to join"))?;
+    for frame in frames {
+        let present: Vec<String> = joined
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let columns: Vec<String> = frame
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .filter(|name| name == key || !present.contains(name))
+            .collect();
+        let frame = frame.select(columns)?;
+        joined = joined.left_join(&frame, [key], [key])?;
+    }
+    Ok(joined)
+}
+
+/// Every player tracking measure and the player hustle stats, one row per
+/// player.
+pub fn player_tracking_df(prepared: &Path) -> PolarsResult<DataFrame> {
+    let mut frames = vec![read_player_tracking(prepared, TRACKING_MEASURES[0])?];
+    for measure in &TRACKING_MEASURES[1..] {
+        frames.push(read_player_tracking(prepared, measure)?.drop_many(PLAYER_TRACKING_IDENTITY));
+    }
+    frames.push(read_player_hustle(prepared)?);
+    join_on(frames, "PLAYER_ID")
+}
+
+/// Every team tracking measure and the team hustle stats, one row per team.
+pub fn team_tracking_df(prepared: &Path) -> PolarsResult<DataFrame> {
+    let mut frames = vec![read_team_tracking(prepared, TRACKING_MEASURES[0])?];
+    for measure in &TRACKING_MEASURES[1..] {
+        frames.push(read_team_tracking(prepared, measure)?.drop_many(TEAM_TRACKING_IDENTITY));
+    }
+    frames.push(read_team_hustle(prepared)?);
+    join_on(frames, "TEAM_ID")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_fetcher::{
+        query::{PtMeasureType, StatsQuery},
+        tracking::{fetch_hustle, fetch_tracking},
+        transport::FixtureTransport,
+        Endpoint, FetchContext, OutputDirs,
+    };
+    use std::{env, fs, sync::Arc};
+
+    /// Fetches every tracking measure and the hustle stats from the seed
+    /// data into a fresh directory under `name`.
+    async fn fetch_seed_tracking(name: &str) -> OutputDirs {
+        let dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        let dirs = OutputDirs::under(&dir);
+        dirs.create().unwrap();
+        let ctx = FetchContext::new(StatsQuery::default(), dirs.clone())
+            .with_transport(Arc::new(FixtureTransport::seed_data()));
+        for measure in PtMeasureType::ALL {
+            fetch_tracking(Endpoint::PlayerTracking(measure), &ctx)
+                .await
+                .unwrap();
+            fetch_tracking(Endpoint::TeamTracking(measure), &ctx)
+                .await
+                .unwrap();
+        }
+        fetch_hustle(Endpoint::PlayerHustle, &ctx).await.unwrap();
+        fetch_hustle(Endpoint::TeamHustle, &ctx).await.unwrap();
+        dirs
+    }
+
+    #[tokio::test]
+    async fn test_read_tracking_data() {
+        let dirs = fetch_seed_tracking("nba_wrangler_read_tracking").await;
+        for measure in TRACKING_MEASURES {
+            println!("{}", read_player_tracking(&dirs.prepared, measure).unwrap());
+            println!("{}", read_team_tracking(&dirs.prepared, measure).unwrap());
+        }
+        println!("{}", read_player_hustle(&dirs.prepared).unwrap());
+        println!("{}", read_team_hustle(&dirs.prepared).unwrap());
+        assert!(read_player_tracking(&dirs.prepared, "Unknown").is_err());
+
+        fs::remove_dir_all(dirs.prepared.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tracking_dfs_have_one_row_per_player_and_team() {
+        let dirs = fetch_seed_tracking("nba_wrangler_tracking_dfs").await;
+        let prepared = &dirs.prepared;
+
+        let players = player_tracking_df(prepared).unwrap();
+        assert_eq!(
+            players.height(),
+            read_player_tracking(prepared, TRACKING_MEASURES[0])
+                .unwrap()
+                .height()
+        );
+        let columns = players.get_column_names();
+        assert!(columns.iter().any(|c| c.as_str() == "DRIVES"));
+        assert!(columns.iter().any(|c| c.as_str() == "SCREEN_ASSISTS"));
+
+        let teams = team_tracking_df(prepared).unwrap();
+        assert_eq!(
+            teams.height(),
+            read_team_tracking(prepared, "Drives").unwrap().height()
+        );
+
+        fs::remove_dir_all(prepared.parent().unwrap()).unwrap();
+    }
+}
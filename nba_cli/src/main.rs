@@ -1,9 +1,25 @@
+use data_fetcher::backfill::{backfill_seasons, BACKFILL_ENDPOINTS};
+use data_fetcher::box_scores::BOX_SCORE_ENDPOINTS;
+use data_fetcher::cache::{http_cache_file_path, CachingTransport};
+use data_fetcher::client::{init_shared_client, ClientConfig};
 use data_fetcher::gather_and_prepare_fetched_data;
+use data_fetcher::live::{LivePoller, PollConfig};
+use data_fetcher::odds_history::{odds_history_file_path, run_odds_capture, OddsHistory};
+use data_fetcher::schema::SchemaRegistry;
+use data_fetcher::{Endpoint, FetchContext, OutputDirs, DAILY_ENDPOINTS, OPT_IN_ENDPOINTS};
+use data_fetcher::per_game::{completed_game_ids, fetch_games};
+use data_fetcher::pipeline::{FetchReport, DEFAULT_CONCURRENCY};
+use data_fetcher::query::{Season, SeasonType, StatsQuery};
+use data_fetcher::transport::{http_transport, FixtureTransport, Transport};
 use dotenv::dotenv;
 use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 fn init_tracing() -> WorkerGuard {
     dotenv().ok(); // Load .env file
@@ -39,6 +55,179 @@ async fn main() {
 
     tracing::info!("Starting the NBA prediction engine");
 
-    // Call your data fetching functions
-    gather_and_prepare_fetched_data().await;
+    let client_config = ClientConfig::from_env().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    init_shared_client(client_config);
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    // Serve every response from saved fixtures instead of the network,
+    // e.g. NBA_FIXTURE_DIR=../data/seed_data. Otherwise responses are cached
+    // under ../data/http_cache unless NBA_HTTP_CACHE=off.
+    let transport: Arc<dyn Transport> = match env::var("NBA_FIXTURE_DIR") {
+        Ok(dir) => Arc::new(FixtureTransport::new(dir)),
+        Err(_) if env::var("NBA_HTTP_CACHE").as_deref() == Ok("off") => http_transport(),
+        Err(_) => Arc::new(CachingTransport::new(http_cache_file_path())),
+    };
+    let concurrency = env::var("NBA_FETCH_CONCURRENCY")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(DEFAULT_CONCURRENCY);
+
+    match args.first().map(String::as_str) {
+        // nba_cli backfill <from season> <to season> [playoffs]
+        Some("backfill") => {
+            let from: Season = parse_arg(&args, 1, "season")
+                .unwrap_or_else(|| usage_error("backfill needs a start season, e.g. 2019-20"));
+            let to: Season = parse_arg(&args, 2, "season").unwrap_or_else(Season::current);
+            let season_type = match args.get(3).map(String::as_str) {
+                Some("playoffs") => SeasonType::Playoffs,
+                _ => SeasonType::RegularSeason,
+            };
+            tracing::info!("Backfilling seasons {} to {}", from, to);
+            let endpoints = with_opt_ins(&BACKFILL_ENDPOINTS);
+            let reports = backfill_seasons(from, to, season_type, &endpoints, transport, concurrency)
+                .await
+                .unwrap();
+            let mut all_ok = true;
+            for (season, report) in reports {
+                print_report(&format!("Backfill {}", season), &report);
+                all_ok &= report.is_success();
+            }
+            if !all_ok {
+                std::process::exit(1);
+            }
+        }
+        // nba_cli box-scores|play-by-play [season]: per-game data for every
+        // finished game in the season's schedule that isn't on disk yet
+        Some(mode @ ("box-scores" | "play-by-play")) => {
+            let season: Season = parse_arg(&args, 1, "season").unwrap_or_else(Season::current);
+            let schedule_dirs = if season == Season::current() {
+                OutputDirs::default()
+            } else {
+                OutputDirs::under(&Endpoint::history_data_file_path(
+                    season,
+                    SeasonType::RegularSeason,
+                ))
+            };
+            let schedule = schedule_dirs
+                .prepared
+                .join(Endpoint::SeasonSchedule.file_name())
+                .with_extension(Endpoint::SeasonSchedule.file_extension());
+            let game_ids = completed_game_ids(&schedule)
+                .unwrap_or_else(|e| panic!("Could not read the schedule at {:?}: {}", schedule, e));
+            let (endpoints, root) = match mode {
+                "box-scores" => (&BOX_SCORE_ENDPOINTS[..], Endpoint::box_scores_file_path(season)),
+                _ => (&[Endpoint::PlayByPlay][..], Endpoint::play_by_play_file_path(season)),
+            };
+            let query = StatsQuery::new().with_season(season);
+            let ctx = FetchContext::new(query, OutputDirs::under(&root)).with_transport(transport);
+            let report = fetch_games(&game_ids, endpoints, &ctx, concurrency)
+                .await
+                .unwrap();
+            tracing::info!("{} {}:\n{}", mode, season, report);
+            println!("{} {}:\n{}", mode, season, report);
+            if !report.is_success() {
+                std::process::exit(1);
+            }
+        }
+        // nba_cli record-schemas: accept the last fetch's response shapes as
+        // the expected ones, e.g. after reviewing a drift report
+        Some("record-schemas") => {
+            let registry = SchemaRegistry::default();
+            let dirs = OutputDirs::default();
+            // Opt-in endpoints are recorded when the last fetch included them
+            let opted_in = OPT_IN_ENDPOINTS
+                .into_iter()
+                .filter(|&endpoint| dirs.raw_response(endpoint).exists());
+            for endpoint in DAILY_ENDPOINTS.into_iter().chain(opted_in) {
+                let path = dirs.raw_response(endpoint);
+                match registry.record_from_file(endpoint, &path) {
+                    Ok(()) => println!("Recorded {:?}", endpoint),
+                    Err(e) => {
+                        eprintln!("Could not record {:?} from {:?}: {}", endpoint, path, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        // nba_cli live: follow today's games and print every change
+        Some("live") => {
+            let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::default())
+                .with_transport(transport);
+            let poller = LivePoller::new(ctx, PollConfig::default());
+            let mut events = poller.subscribe();
+            let printer = tokio::spawn(async move {
+                while let Ok(event) = events.recv().await {
+                    println!("{:?}", event);
+                }
+            });
+            poller.run().await.unwrap();
+            printer.await.unwrap();
+        }
+        // nba_cli odds-capture [minutes]: append every odds move to the
+        // history store, checking every few minutes until stopped
+        Some("odds-capture") => {
+            let minutes: u64 = parse_arg(&args, 1, "interval in minutes").unwrap_or(5);
+            if minutes == 0 {
+                usage_error("odds-capture needs an interval of at least one minute");
+            }
+            let ctx = FetchContext::new(StatsQuery::default(), OutputDirs::default())
+                .with_transport(transport);
+            let history = OddsHistory::open(odds_history_file_path()).unwrap();
+            tracing::info!("Capturing odds every {} minutes", minutes);
+            run_odds_capture(ctx, history, Duration::from_secs(minutes * 60))
+                .await
+                .unwrap();
+        }
+        // Call your data fetching functions
+        _ => {
+            let endpoints = with_opt_ins(&DAILY_ENDPOINTS);
+            let report = gather_and_prepare_fetched_data(&StatsQuery::default(), &endpoints, transport, concurrency)
+                .await
+                .unwrap();
+            print_report("Fetch", &report);
+            if !report.is_success() {
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// `endpoints` and, with NBA_FETCH_SHOT_CHART=on, the league-wide shot chart.
+fn with_opt_ins(endpoints: &[Endpoint]) -> Vec<Endpoint> {
+    let mut endpoints = endpoints.to_vec();
+    if env::var("NBA_FETCH_SHOT_CHART").as_deref() == Ok("on") {
+        endpoints.extend(OPT_IN_ENDPOINTS);
+    }
+    endpoints
+}
+
+const USAGE: &str = "usage: nba_cli [backfill <from season> [to season] [playoffs] \
+    | box-scores [season] | play-by-play [season] | record-schemas | live \
+    | odds-capture [minutes]]";
+
+/// The argument at `index` parsed as a `T`, or `None` if there isn't one.
+/// Exits with the usage message if it doesn't parse.
+fn parse_arg<T>(args: &[String], index: usize, what: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let arg = args.get(index)?;
+    match arg.parse() {
+        Ok(value) => Some(value),
+        Err(e) => usage_error(&format!("Invalid {} {:?}: {}", what, arg, e)),
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    std::process::exit(2);
+}
+
+fn print_report(title: &str, report: &FetchReport) {
+    tracing::info!("{} report:\n{}", title, report);
+    println!("{}:\n{}", title, report);
 }